@@ -0,0 +1,56 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+use super::{
+    Authenticator,
+    HttpAuthContext,
+    HttpChallenge,
+};
+
+
+/// Basic access authentication (RFC 7617).
+/// Credentials sends with the first request without server challenge
+pub struct BasicAuth {
+    username: String,
+    /// base64 encoded `username:password`
+    credentials: String,
+}
+
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("username", &self.username)
+            .finish()
+    }
+}
+
+
+impl BasicAuth {
+    /// Allocates new Basic authenticator
+    pub fn new(username: &str, password: &str) -> Self {
+        BasicAuth {
+            username: username.to_owned(),
+            credentials: base64::encode(&format!("{}:{}", username, password)),
+        }
+    }
+}
+
+
+impl Authenticator for BasicAuth {
+    #[inline]
+    fn rank(&self, challenge: &HttpChallenge) -> usize {
+        if challenge.is_scheme("basic") { 1 } else { 0 }
+    }
+
+    #[inline]
+    fn authorize(&mut self, _context: &HttpAuthContext) -> Option<String> {
+        Some(format!("Basic {}", &self.credentials))
+    }
+}
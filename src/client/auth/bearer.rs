@@ -0,0 +1,97 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+use super::{
+    Authenticator,
+    HttpAuthContext,
+    HttpChallenge,
+};
+
+
+type RefreshFn = Box<dyn FnMut() -> Option<String> + Send>;
+
+
+/// Bearer token authentication (RFC 6750).
+/// Token sends with the first request without server challenge.
+/// If server rejects token, new one could be obtained with refresh callback
+///
+/// Usage:
+///
+/// ```no_run
+/// use http::{
+///     BearerAuth,
+///     HttpClient,
+/// };
+///
+/// let mut auth = BearerAuth::new("initial-token");
+/// auth.set_refresh(|| {
+///     // request new token from the authorization server
+///     Some("new-token".to_owned())
+/// });
+///
+/// let mut client = HttpClient::new("http://api.local/v1/status").unwrap();
+/// client.add_authenticator(auth);
+/// client.get().unwrap();
+/// ```
+pub struct BearerAuth {
+    token: String,
+    refresh: Option<RefreshFn>,
+}
+
+
+impl fmt::Debug for BearerAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BearerAuth")
+            .field("refresh", &self.refresh.is_some())
+            .finish()
+    }
+}
+
+
+impl BearerAuth {
+    /// Allocates new Bearer authenticator with access token
+    pub fn new(token: &str) -> Self {
+        BearerAuth {
+            token: token.to_owned(),
+            refresh: None,
+        }
+    }
+
+    /// Sets callback to obtain new token when server responds with 401.
+    /// Callback returns None if token could not be refreshed
+    pub fn set_refresh<F>(&mut self, f: F)
+    where
+        F: FnMut() -> Option<String> + Send + 'static,
+    {
+        self.refresh = Some(Box::new(f))
+    }
+}
+
+
+impl Authenticator for BearerAuth {
+    #[inline]
+    fn rank(&self, challenge: &HttpChallenge) -> usize {
+        if challenge.is_scheme("bearer") { 1 } else { 0 }
+    }
+
+    fn authorize(&mut self, context: &HttpAuthContext) -> Option<String> {
+        if context.challenge.is_some() {
+            // token rejected by server
+            if let Some(refresh) = self.refresh.as_mut() {
+                self.token = refresh()?;
+            }
+        }
+
+        if self.token.is_empty() {
+            None
+        } else {
+            Some(format!("Bearer {}", &self.token))
+        }
+    }
+}
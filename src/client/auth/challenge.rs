@@ -0,0 +1,254 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+
+/// Authentication challenge (RFC 7235)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HttpChallenge {
+    scheme: String,
+    token68: String,
+    params: Vec<(String, String)>,
+}
+
+
+impl HttpChallenge {
+    /// Returns authentication scheme
+    #[inline]
+    pub fn get_scheme(&self) -> &str { &self.scheme }
+
+    /// Checks authentication scheme, case insensitive
+    #[inline]
+    pub fn is_scheme(&self, scheme: &str) -> bool { self.scheme.eq_ignore_ascii_case(scheme) }
+
+    /// Returns token68 value, e.g. for `Negotiate` scheme. Empty if not defined
+    #[inline]
+    pub fn get_token68(&self) -> &str { &self.token68 }
+
+    /// Returns parameter value. Name is case insensitive
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+
+#[inline]
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+
+#[inline]
+fn is_token68(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~+/".contains(&b)
+}
+
+
+struct Parser<'a> {
+    data: &'a str,
+    pos: usize,
+}
+
+
+impl<'a> Parser<'a> {
+    #[inline]
+    fn peek(&self) -> Option<u8> { self.data.as_bytes().get(self.pos).copied() }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_ws_comma(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b',') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn take_while<F: Fn(u8) -> bool>(&mut self, f: F) -> &'a str {
+        let begin = self.pos;
+        while self.peek().map(&f).unwrap_or(false) {
+            self.pos += 1;
+        }
+        &self.data[begin .. self.pos]
+    }
+
+    /// Reads quoted-string with escaped symbols
+    fn quoted_string(&mut self) -> String {
+        let mut result = String::new();
+        self.pos += 1;
+
+        let mut escape = false;
+        for (i, c) in self.data[self.pos ..].char_indices() {
+            if escape {
+                result.push(c);
+                escape = false;
+            } else if c == '\\' {
+                escape = true;
+            } else if c == '"' {
+                self.pos += i + 1;
+                return result
+            } else {
+                result.push(c);
+            }
+        }
+
+        // unterminated string
+        self.pos = self.data.len();
+        result
+    }
+
+    /// Reads token68 if it is a whole challenge value
+    fn token68(&mut self) -> Option<&'a str> {
+        let begin = self.pos;
+        let value = self.take_while(is_token68);
+        if ! value.is_empty() {
+            let end = self.take_while(|b| b == b'=');
+            let value = &self.data[begin .. begin + value.len() + end.len()];
+            self.skip_ws();
+            if let None | Some(b',') = self.peek() {
+                return Some(value)
+            }
+        }
+
+        self.pos = begin;
+        None
+    }
+
+    /// Reads `name = value` parameter.
+    /// Returns None if next item is not a parameter
+    fn param(&mut self) -> Option<(String, String)> {
+        let begin = self.pos;
+        let name = self.take_while(is_tchar);
+        self.skip_ws();
+        if name.is_empty() || self.peek() != Some(b'=') {
+            self.pos = begin;
+            return None
+        }
+
+        self.pos += 1;
+        self.skip_ws();
+
+        let value = if self.peek() == Some(b'"') {
+            self.quoted_string()
+        } else {
+            self.take_while(is_tchar).to_owned()
+        };
+
+        Some((name.to_owned(), value))
+    }
+
+    fn challenge(&mut self) -> Option<HttpChallenge> {
+        let scheme = self.take_while(is_tchar);
+        if scheme.is_empty() {
+            // skip invalid symbol
+            self.pos += self.data[self.pos ..].chars().next().map(char::len_utf8).unwrap_or(0);
+            return None
+        }
+
+        let mut challenge = HttpChallenge {
+            scheme: scheme.to_owned(),
+            .. HttpChallenge::default()
+        };
+
+        self.skip_ws();
+        if let Some(token68) = self.token68() {
+            challenge.token68 = token68.to_owned();
+            return Some(challenge)
+        }
+
+        while let Some(param) = self.param() {
+            challenge.params.push(param);
+            self.skip_ws();
+            if self.peek() != Some(b',') {
+                break
+            }
+            self.skip_ws_comma();
+        }
+
+        Some(challenge)
+    }
+}
+
+
+/// Parses WWW-Authenticate or Proxy-Authenticate header value.
+/// Value could contain several challenges
+pub fn parse_challenges(value: &str, list: &mut Vec<HttpChallenge>) {
+    let mut parser = Parser { data: value, pos: 0 };
+
+    loop {
+        parser.skip_ws_comma();
+        if parser.peek().is_none() {
+            break
+        }
+
+        if let Some(challenge) = parser.challenge() {
+            list.push(challenge);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: &str) -> Vec<HttpChallenge> {
+        let mut list = Vec::new();
+        parse_challenges(value, &mut list);
+        list
+    }
+
+    #[test]
+    fn test_single() {
+        let list = parse("Basic realm=\"camera\"");
+        assert_eq!(list.len(), 1);
+        assert!(list[0].is_scheme("basic"));
+        assert_eq!(list[0].get_param("REALM"), Some("camera"));
+    }
+
+    #[test]
+    fn test_multiple() {
+        let list = parse(concat!(
+            "Newauth realm=\"apps\", type=1, title=\"Login to \\\"apps\\\"\", ",
+            "Basic realm=\"simple, with comma\",",
+            "Digest realm=\"x\", qop=\"auth,auth-int\", nonce=abc"));
+        assert_eq!(list.len(), 3);
+
+        assert_eq!(list[0].get_scheme(), "Newauth");
+        assert_eq!(list[0].get_param("type"), Some("1"));
+        assert_eq!(list[0].get_param("title"), Some("Login to \"apps\""));
+
+        assert_eq!(list[1].get_scheme(), "Basic");
+        assert_eq!(list[1].get_param("realm"), Some("simple, with comma"));
+
+        assert_eq!(list[2].get_scheme(), "Digest");
+        assert_eq!(list[2].get_param("qop"), Some("auth,auth-int"));
+        assert_eq!(list[2].get_param("nonce"), Some("abc"));
+    }
+
+    #[test]
+    fn test_token68() {
+        let list = parse("Negotiate, NTLM TlRMTVNTUAACAAAA==, Bearer realm=\"api\"");
+        assert_eq!(list.len(), 3);
+        assert!(list[0].is_scheme("negotiate"));
+        assert_eq!(list[0].get_token68(), "");
+        assert_eq!(list[1].get_token68(), "TlRMTVNTUAACAAAA==");
+        assert_eq!(list[2].get_param("realm"), Some("api"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse("").is_empty());
+        assert!(parse(" , ,").is_empty());
+        let list = parse("Basic realm=\"unterminated");
+        assert_eq!(list[0].get_param("realm"), Some("unterminated"));
+    }
+}
@@ -0,0 +1,449 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt::{
+    self,
+    Write,
+};
+
+use openssl::{
+    hash::{
+        Hasher,
+        MessageDigest,
+    },
+    rand::rand_bytes,
+};
+
+use super::{
+    Authenticator,
+    HttpAuthContext,
+    HttpChallenge,
+};
+
+
+static HEXMAP: &[u8] = b"0123456789abcdef";
+
+
+fn hex2string<R: AsRef<[u8]>>(bytes: R) -> String {
+    let bytes = bytes.as_ref();
+    let mut ret = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        ret.push(char::from(HEXMAP[(b >> 4) as usize]));
+        ret.push(char::from(HEXMAP[(b & 0x0F) as usize]));
+    }
+    ret
+}
+
+
+/// Escapes quotes and backslashes for the quoted-string
+fn quote(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+
+/// Hash algorithm for Digest authentication (RFC 7616)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DigestAlgorithm {
+    Md5,
+    Sha256,
+    Sha512_256,
+}
+
+
+impl DigestAlgorithm {
+    /// Parses algorithm parameter. Returns algorithm and session flag
+    fn parse(value: &str) -> Option<(Self, bool)> {
+        let (name, sess) = match value.len().checked_sub(5) {
+            // compare bytes, value could be not ASCII
+            Some(skip) if value.as_bytes()[skip ..].eq_ignore_ascii_case(b"-sess") => (&value[.. skip], true),
+            _ => (value, false),
+        };
+
+        let algorithm = if name.eq_ignore_ascii_case("MD5") {
+            DigestAlgorithm::Md5
+        } else if name.eq_ignore_ascii_case("SHA-256") {
+            DigestAlgorithm::Sha256
+        } else if name.eq_ignore_ascii_case("SHA-512-256") {
+            DigestAlgorithm::Sha512_256
+        } else {
+            return None
+        };
+
+        Some((algorithm, sess))
+    }
+
+    fn message_digest(self) -> MessageDigest {
+        match self {
+            DigestAlgorithm::Md5 => MessageDigest::md5(),
+            DigestAlgorithm::Sha256 => MessageDigest::sha256(),
+            DigestAlgorithm::Sha512_256 => MessageDigest::from_name("SHA512-256").unwrap(),
+        }
+    }
+
+    /// Returns hex-encoded hash of the joined parts
+    fn hash(self, parts: &[&[u8]]) -> String {
+        let mut h = Hasher::new(self.message_digest()).unwrap();
+        parts.iter().for_each(|s| h.update(s).unwrap());
+        hex2string(h.finish().unwrap())
+    }
+}
+
+
+/// Returns strength of the Digest challenge.
+/// Zero if algorithm or qop is not supported
+fn rank(challenge: &HttpChallenge) -> usize {
+    if let Some(qop) = challenge.get_param("qop") {
+        if select_qop(qop).is_none() {
+            return 0
+        }
+    }
+
+    match challenge.get_param("algorithm").map(DigestAlgorithm::parse) {
+        None => 2,
+        Some(Some((DigestAlgorithm::Md5, _))) => 2,
+        Some(Some((DigestAlgorithm::Sha256, _))) => 3,
+        Some(Some((DigestAlgorithm::Sha512_256, _))) => {
+            // could be not available in old OpenSSL versions
+            if MessageDigest::from_name("SHA512-256").is_some() { 4 } else { 0 }
+        }
+        Some(None) => 0,
+    }
+}
+
+
+/// Checks that nonce is expired and request could be repeated with new nonce
+#[inline]
+pub fn is_stale(challenge: &HttpChallenge) -> bool {
+    challenge.get_param("stale").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+
+/// Selects quality of protection from the comma-separated list.
+/// `auth` is preferred
+fn select_qop(list: &str) -> Option<&'static str> {
+    let mut result = None;
+
+    for qop in list.split(',').map(|v| v.trim()) {
+        if qop.eq_ignore_ascii_case("auth") {
+            return Some("auth")
+        }
+        if qop.eq_ignore_ascii_case("auth-int") {
+            result = Some("auth-int");
+        }
+    }
+
+    result
+}
+
+
+/// Values for the response calculation
+struct DigestResponse<'a> {
+    algorithm: DigestAlgorithm,
+    sess: bool,
+    username: &'a str,
+    password: &'a str,
+    realm: &'a str,
+    nonce: &'a str,
+    cnonce: &'a str,
+    nc: &'a str,
+    qop: &'a str,
+    method: &'a str,
+    uri: &'a str,
+    body: &'a [u8],
+}
+
+
+impl<'a> DigestResponse<'a> {
+    fn calculate(&self) -> String {
+        let a = self.algorithm;
+
+        let mut ha1 = a.hash(&[
+            self.username.as_bytes(), b":",
+            self.realm.as_bytes(), b":",
+            self.password.as_bytes(),
+        ]);
+
+        if self.sess {
+            ha1 = a.hash(&[
+                ha1.as_bytes(), b":",
+                self.nonce.as_bytes(), b":",
+                self.cnonce.as_bytes(),
+            ]);
+        }
+
+        let ha2 = if self.qop == "auth-int" {
+            let hbody = a.hash(&[self.body]);
+            a.hash(&[
+                self.method.as_bytes(), b":",
+                self.uri.as_bytes(), b":",
+                hbody.as_bytes(),
+            ])
+        } else {
+            a.hash(&[
+                self.method.as_bytes(), b":",
+                self.uri.as_bytes(),
+            ])
+        };
+
+        if self.qop.is_empty() {
+            a.hash(&[
+                ha1.as_bytes(), b":",
+                self.nonce.as_bytes(), b":",
+                ha2.as_bytes(),
+            ])
+        } else {
+            a.hash(&[
+                ha1.as_bytes(), b":",
+                self.nonce.as_bytes(), b":",
+                self.nc.as_bytes(), b":",
+                self.cnonce.as_bytes(), b":",
+                self.qop.as_bytes(), b":",
+                ha2.as_bytes(),
+            ])
+        }
+    }
+}
+
+
+/// Digest Access Authentication (RFC 7616).
+/// Keeps last challenge and nonce count between requests
+pub struct DigestAuth {
+    username: String,
+    password: String,
+    /// last challenge from the server
+    challenge: Option<HttpChallenge>,
+    /// last nonce
+    nonce: String,
+    nonce_count: u32,
+}
+
+
+impl fmt::Debug for DigestAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DigestAuth")
+            .field("username", &self.username)
+            .field("nonce", &self.nonce)
+            .field("nonce_count", &self.nonce_count)
+            .finish()
+    }
+}
+
+
+impl DigestAuth {
+    /// Allocates new Digest authenticator
+    pub fn new(username: &str, password: &str) -> Self {
+        DigestAuth {
+            username: username.to_owned(),
+            password: password.to_owned(),
+            challenge: None,
+            nonce: String::new(),
+            nonce_count: 0,
+        }
+    }
+
+    /// Returns value for Authorization header.
+    /// `body` is a request body for `qop=auth-int`
+    fn digest(&mut self, method: &str, uri: &str, challenge: &HttpChallenge, body: &[u8]) -> String {
+        let mut result = String::with_capacity(1024);
+
+        let username = self.username.as_str();
+        let password = self.password.as_str();
+
+        let realm = challenge.get_param("realm").unwrap_or("");
+        let nonce = challenge.get_param("nonce").unwrap_or("");
+        let qop = challenge.get_param("qop").and_then(select_qop).unwrap_or("");
+
+        let (algorithm, sess) = challenge.get_param("algorithm")
+            .and_then(DigestAlgorithm::parse)
+            .unwrap_or((DigestAlgorithm::Md5, false));
+
+        let userhash = challenge.get_param("userhash")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        if userhash {
+            let hash = algorithm.hash(&[username.as_bytes(), b":", realm.as_bytes()]);
+            write!(result, "Digest username=\"{}\", uri=\"{}\"", hash, quote(uri)).unwrap();
+        } else {
+            write!(result, "Digest username=\"{}\", uri=\"{}\"", quote(username), quote(uri)).unwrap();
+        }
+
+        if ! realm.is_empty() {
+            write!(result, ", realm=\"{}\"", quote(realm)).unwrap();
+        }
+        if ! nonce.is_empty() {
+            write!(result, ", nonce=\"{}\"", quote(nonce)).unwrap();
+        }
+        if ! qop.is_empty() {
+            write!(result, ", qop=\"{}\"", qop).unwrap();
+        }
+        if let Some(opaque) = challenge.get_param("opaque") {
+            write!(result, ", opaque=\"{}\"", quote(opaque)).unwrap();
+        }
+        if algorithm != DigestAlgorithm::Md5 || sess {
+            write!(result, ", algorithm={}", challenge.get_param("algorithm").unwrap_or("")).unwrap();
+        }
+        if userhash {
+            result.push_str(", userhash=true");
+        }
+
+        // nonce count starts again for each new nonce
+        if self.nonce != nonce {
+            self.nonce = nonce.to_owned();
+            self.nonce_count = 0;
+        }
+
+        let mut nc = String::new();
+        let mut cnonce = String::new();
+
+        if ! qop.is_empty() || sess {
+            cnonce = {
+                let mut buf = [0; 4];
+                rand_bytes(&mut buf).unwrap();
+                hex2string(buf)
+            };
+        }
+
+        if ! qop.is_empty() {
+            self.nonce_count = self.nonce_count.checked_add(1).unwrap_or(1);
+            nc = format!("{:08x}", self.nonce_count);
+
+            write!(result, ", nc={}, cnonce=\"{}\"", &nc, &cnonce).unwrap();
+        } else if sess {
+            write!(result, ", cnonce=\"{}\"", &cnonce).unwrap();
+        }
+
+        let response = DigestResponse {
+            algorithm,
+            sess,
+            username,
+            password,
+            realm,
+            nonce,
+            cnonce: &cnonce,
+            nc: &nc,
+            qop,
+            method,
+            uri,
+            body,
+        };
+
+        write!(result, ", response=\"{}\"", response.calculate()).unwrap();
+
+        result
+    }
+}
+
+
+impl Authenticator for DigestAuth {
+    fn rank(&self, challenge: &HttpChallenge) -> usize {
+        if challenge.is_scheme("digest") { rank(challenge) } else { 0 }
+    }
+
+    /// Digest requires server challenge.
+    /// Next requests reuse last challenge with incremented nonce count
+    fn authorize(&mut self, context: &HttpAuthContext) -> Option<String> {
+        if let Some(challenge) = context.challenge {
+            self.challenge = Some(challenge.clone());
+        }
+
+        let challenge = self.challenge.take()?;
+        let result = self.digest(context.request.get_method(), context.uri, &challenge, context.body);
+        self.challenge = Some(challenge);
+
+        Some(result)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::auth::challenge::parse_challenges;
+
+    /// RFC 7616 3.9.1
+    fn rfc7616(algorithm: DigestAlgorithm) -> DigestResponse<'static> {
+        DigestResponse {
+            algorithm,
+            sess: false,
+            username: "Mufasa",
+            password: "Circle of Life",
+            realm: "http-auth@example.org",
+            nonce: "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+            cnonce: "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+            nc: "00000001",
+            qop: "auth",
+            method: "GET",
+            uri: "/dir/index.html",
+            body: b"",
+        }
+    }
+
+    #[test]
+    fn test_md5() {
+        assert_eq!(
+            rfc7616(DigestAlgorithm::Md5).calculate(),
+            "8ca523f5e9506fed4657c9700eebdbec");
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            rfc7616(DigestAlgorithm::Sha256).calculate(),
+            "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1");
+    }
+
+    #[test]
+    fn test_sha512_256() {
+        assert_eq!(
+            DigestAlgorithm::Sha512_256.hash(&[b"abc"]),
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(DigestAlgorithm::parse("MD5"), Some((DigestAlgorithm::Md5, false)));
+        assert_eq!(DigestAlgorithm::parse("sha-256-sess"), Some((DigestAlgorithm::Sha256, true)));
+        assert_eq!(DigestAlgorithm::parse("SHA-512-256"), Some((DigestAlgorithm::Sha512_256, false)));
+        assert_eq!(DigestAlgorithm::parse("SHA-1"), None);
+        assert_eq!(DigestAlgorithm::parse("éabcd"), None);
+        assert_eq!(DigestAlgorithm::parse("é-sess"), None);
+
+        assert_eq!(select_qop("auth-int, auth"), Some("auth"));
+        assert_eq!(select_qop("auth-int"), Some("auth-int"));
+        assert_eq!(select_qop("token"), None);
+    }
+
+    #[test]
+    fn test_nonce_count() {
+        let mut list = Vec::new();
+        parse_challenges("Digest realm=\"test\", nonce=\"n1\", qop=\"auth\"", &mut list);
+
+        let mut auth = DigestAuth::new("test", "pass");
+        auth.nonce_count = 9;
+        auth.nonce = "n1".to_owned();
+        let result = auth.digest("GET", "/", &list[0], b"");
+        assert!(result.contains(", nc=0000000a, "));
+    }
+
+    #[test]
+    fn test_quote() {
+        let mut list = Vec::new();
+        parse_challenges(r#"Digest realm="my \"cam\"", nonce="n1", opaque="a\\b""#, &mut list);
+
+        let mut auth = DigestAuth::new("te\"st", "pass");
+        let result = auth.digest("GET", "/", &list[0], b"");
+        assert!(result.starts_with(r#"Digest username="te\"st", uri="/", realm="my \"cam\"", nonce="n1", opaque="a\\b""#));
+    }
+}
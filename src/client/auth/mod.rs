@@ -0,0 +1,250 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+use crate::{
+    request::Request,
+    response::Response,
+};
+
+mod challenge;
+pub use self::challenge::HttpChallenge;
+use self::challenge::parse_challenges;
+
+mod basic;
+pub use self::basic::BasicAuth;
+
+mod bearer;
+pub use self::bearer::BearerAuth;
+
+mod digest;
+pub use self::digest::DigestAuth;
+
+
+#[derive(Debug, Error)]
+#[error_prefix = "Auth"]
+pub enum HttpAuthError {
+    #[error_kind("unsupported scheme: {}", 0)]
+    Unsupported(String),
+}
+
+
+pub type Result<T> = std::result::Result<T, HttpAuthError>;
+
+
+/// Request details for the authenticator
+pub struct HttpAuthContext<'a> {
+    /// Request with method and headers
+    pub request: &'a Request,
+    /// Request target: path for origin server,
+    /// absolute URI or `host:port` for proxy server
+    pub uri: &'a str,
+    /// Request body
+    pub body: &'a [u8],
+    /// Challenge from the server response.
+    /// None if server not requested authentication yet
+    /// or request repeats with the previously selected authenticator
+    pub challenge: Option<&'a HttpChallenge>,
+}
+
+
+/// Authentication scheme for HttpClient
+///
+/// Called before each request to get value for the Authorization header.
+/// On 401 and 407 responses, the authenticator with the strongest
+/// supported challenge is called again to repeat request.
+/// Next requests without challenge use the same authenticator.
+///
+/// Custom schemes, e.g. HMAC-signed requests, implements this trait
+/// and registers with `HttpClient::add_authenticator()`
+pub trait Authenticator: fmt::Debug + Send {
+    /// Returns strength of the supported challenge.
+    /// Zero if challenge is not supported
+    fn rank(&self, challenge: &HttpChallenge) -> usize;
+
+    /// Returns value for the Authorization header.
+    /// None if request could not be authorized
+    fn authorize(&mut self, context: &HttpAuthContext) -> Option<String>;
+}
+
+
+/// Set of authenticators with state between requests
+#[derive(Debug, Default)]
+pub struct HttpAuth {
+    /// username and password for the default authenticators
+    credentials: (String, String),
+    /// Digest and Basic authenticators for credentials
+    default: Vec<Box<dyn Authenticator>>,
+    /// authenticators defined with `push()`
+    custom: Vec<Box<dyn Authenticator>>,
+    /// authenticator for the last server challenge.
+    /// Index in the custom authenticators followed by default
+    selected: Option<usize>,
+}
+
+
+impl HttpAuth {
+    /// Sets credentials for Digest and Basic authentication.
+    /// Empty username disables authentication with credentials.
+    /// Authentication state resets if credentials changed
+    pub fn set_credentials(&mut self, username: &str, password: &str) {
+        if self.credentials.0 == username && self.credentials.1 == password {
+            return
+        }
+
+        self.credentials = (username.to_owned(), password.to_owned());
+        self.default.clear();
+
+        if self.selected.map(|v| v >= self.custom.len()).unwrap_or(false) {
+            self.selected = None;
+        }
+
+        if ! username.is_empty() {
+            self.default.push(Box::new(DigestAuth::new(username, password)));
+            self.default.push(Box::new(BasicAuth::new(username, password)));
+        }
+    }
+
+    /// Appends custom authenticator. Custom authenticators have priority
+    /// over authenticators for credentials
+    pub fn push(&mut self, auth: Box<dyn Authenticator>) {
+        // keep selection of the default authenticator
+        if let Some(i) = self.selected.as_mut() {
+            if *i >= self.custom.len() {
+                *i += 1;
+            }
+        }
+
+        self.custom.push(auth)
+    }
+
+    /// Returns credentials for the strongest supported challenge.
+    /// Empty list means that server not requested authentication yet
+    /// or previous request was accepted.
+    /// In this case the authenticator selected for the last challenge is used,
+    /// without selection first authenticator that supports
+    /// preemptive authentication is used
+    fn authorize(
+        &mut self,
+        request: &Request,
+        uri: &str,
+        challenges: &[HttpChallenge],
+        body: &[u8],
+    ) -> Result<Option<String>>
+    {
+        let mut list: Vec<&mut Box<dyn Authenticator>> = self.custom
+            .iter_mut()
+            .chain(self.default.iter_mut())
+            .collect();
+
+        if list.is_empty() {
+            return Ok(None)
+        }
+
+        let mut context = HttpAuthContext {
+            request,
+            uri,
+            body,
+            challenge: None,
+        };
+
+        if challenges.is_empty() {
+            let result = match self.selected {
+                Some(i) => list[i].authorize(&context),
+                None => list.iter_mut().find_map(|v| v.authorize(&context)),
+            };
+            return Ok(result)
+        }
+
+        // first challenge from the strongest, first authenticator for the challenge
+        let mut selected = None;
+        let mut selected_rank = 0;
+        for challenge in challenges {
+            for (i, auth) in list.iter().enumerate() {
+                let rank = auth.rank(challenge);
+                if rank > selected_rank {
+                    selected = Some((i, challenge));
+                    selected_rank = rank;
+                }
+            }
+        }
+
+        match selected {
+            Some((i, challenge)) => {
+                self.selected = Some(i);
+                context.challenge = Some(challenge);
+                Ok(list[i].authorize(&context))
+            }
+            None => {
+                let list = challenges
+                    .iter()
+                    .map(|v| v.get_scheme())
+                    .collect::<Vec<&str>>()
+                    .join(", ");
+                Err(HttpAuthError::Unsupported(list))
+            }
+        }
+    }
+}
+
+
+/// Returns all challenges from the response header
+fn get_challenges(response: &Response, name: &str) -> Vec<HttpChallenge> {
+    let mut list = Vec::new();
+    for value in response.header.get_all(name) {
+        parse_challenges(value, &mut list);
+    }
+    list
+}
+
+
+/// Checks that server rejected request because of the expired Digest nonce
+pub fn is_stale(response: &Response) -> bool {
+    get_challenges(response, "www-authenticate")
+        .iter()
+        .any(|v| v.is_scheme("digest") && digest::is_stale(v))
+}
+
+
+/// Sets Authorization header.
+/// `body` is a request body for Digest with `qop=auth-int`.
+/// Returns false if request could not be authorized
+pub fn http_auth(auth: &mut HttpAuth, request: &mut Request, response: &Response, body: &[u8]) -> Result<bool> {
+    // RTSP request line contains absolute URI
+    let uri = if request.url.get_scheme() == "rtsp" {
+        request.url.as_url().to_string()
+    } else {
+        request.url.get_path().to_owned()
+    };
+    let challenges = get_challenges(response, "www-authenticate");
+
+    match auth.authorize(request, &uri, &challenges, body)? {
+        Some(value) => {
+            request.header.set("Authorization", value);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+
+/// Sets Proxy-Authorization header with proxy credentials
+pub fn proxy_auth(
+    auth: &mut HttpAuth,
+    request: &mut Request,
+    response: &Response,
+    uri: &str,
+) -> Result<()>
+{
+    let challenges = get_challenges(response, "proxy-authenticate");
+
+    if let Some(value) = auth.authorize(request, uri, &challenges, &[])? {
+        request.header.set("Proxy-Authorization", value);
+    }
+    Ok(())
+}
@@ -6,6 +6,7 @@
 // permission of Cesbo OU
 
 use std::{
+    fmt,
     mem,
     io::{
         self,
@@ -16,25 +17,56 @@ use std::{
     },
 };
 
-use libflate::gzip::Decoder;
-
-use crate::client::transfer::{
-    HttpTransfer,
+use libflate::{
+    deflate,
+    gzip,
+    zlib,
 };
 
+use crate::client::transfer::HttpTransfer;
+
+
+/// Value for the Accept-Encoding header with all supported encodings
+pub const ACCEPT_ENCODING: &str = "gzip, deflate";
+
 
 enum HttpContentInner {
     None,
     Plain(HttpTransfer),
-    Gzip(BufReader<Decoder<HttpTransfer>>),
+    Gzip(BufReader<gzip::Decoder<HttpTransfer>>),
+    Deflate(BufReader<zlib::Decoder<HttpTransfer>>),
+    DeflateRaw(BufReader<deflate::Decoder<HttpTransfer>>),
 }
 
 
+/// HTTP content decoder
+///
+/// Wraps HTTP transport and decodes content defined by
+/// the Content-Encoding header: `gzip`, `x-gzip`, and `deflate`.
+/// `deflate` without zlib wrapper is also supported
 pub struct HttpContent {
     inner: HttpContentInner,
 }
 
 
+impl fmt::Debug for HttpContent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let encoding = match &self.inner {
+            HttpContentInner::Plain(_) => "identity",
+            HttpContentInner::Gzip(_) => "gzip",
+            HttpContentInner::Deflate(_) => "deflate",
+            HttpContentInner::DeflateRaw(_) => "deflate",
+            HttpContentInner::None => "none",
+        };
+
+        f.debug_struct("HttpContent")
+            .field("encoding", &encoding)
+            .field("transfer", self.as_transfer())
+            .finish()
+    }
+}
+
+
 impl Default for HttpContent {
     fn default() -> Self {
         HttpContent {
@@ -44,31 +76,135 @@ impl Default for HttpContent {
 }
 
 
+/// Reads transport until end of content.
+/// Decoder stops reading on the end of the compressed stream
+/// but transport should reach EOF to complete response
+fn drain(transfer: &mut HttpTransfer) -> io::Result<()> {
+    io::copy(transfer, &mut io::sink())?;
+    Ok(())
+}
+
+
+/// Checks zlib header (RFC 1950): deflate method and header checksum
+fn is_zlib(buf: &[u8]) -> bool {
+    buf.len() >= 2 &&
+        (buf[0] & 0x0F) == 8 &&
+        ((u16::from(buf[0]) << 8) | u16::from(buf[1])) % 31 == 0
+}
+
+
 impl HttpContent {
+    /// Returns reference to the HTTP transport
+    pub fn as_transfer(&self) -> &HttpTransfer {
+        match &self.inner {
+            HttpContentInner::Plain(v) => v,
+            HttpContentInner::Gzip(v) => v.get_ref().as_inner_ref(),
+            HttpContentInner::Deflate(v) => v.get_ref().as_inner_ref(),
+            HttpContentInner::DeflateRaw(v) => v.get_ref().as_inner_ref(),
+            HttpContentInner::None => unreachable!(),
+        }
+    }
+
+    /// Returns mutable reference to the HTTP transport
     pub fn as_transfer_mut(&mut self) -> &mut HttpTransfer {
         match &mut self.inner {
             HttpContentInner::Plain(v) => v,
             HttpContentInner::Gzip(v) => v.get_mut().as_inner_mut(),
+            HttpContentInner::Deflate(v) => v.get_mut().as_inner_mut(),
+            HttpContentInner::DeflateRaw(v) => v.get_mut().as_inner_mut(),
             HttpContentInner::None => unreachable!(),
         }
     }
 
-    pub fn set_content_encoding(&mut self, encoding: &str) {
-        let inner = mem::replace(&mut self.inner, HttpContentInner::None);
-        let inner = match inner {
+    fn take_transfer(&mut self) -> HttpTransfer {
+        match mem::replace(&mut self.inner, HttpContentInner::None) {
             HttpContentInner::Plain(v) => v,
             HttpContentInner::Gzip(v) => v.into_inner().into_inner(),
+            HttpContentInner::Deflate(v) => v.into_inner().into_inner(),
+            HttpContentInner::DeflateRaw(v) => v.into_inner().into_inner(),
             HttpContentInner::None => unreachable!(),
+        }
+    }
+
+    /// Sets content decoder by the Content-Encoding header value.
+    /// Should be called after content length or transfer encoding defined
+    /// because decoder reads stream header immediately.
+    /// Unknown and `identity` encodings are passed as is
+    pub fn set_content_encoding(&mut self, encoding: &str) -> io::Result<()> {
+        let mut transfer = self.take_transfer();
+
+        let mut gzip = false;
+        let mut deflate = false;
+        for i in encoding.split(',').map(|v| v.trim()) {
+            if i.eq_ignore_ascii_case("gzip") || i.eq_ignore_ascii_case("x-gzip") {
+                gzip = true;
+            } else if i.eq_ignore_ascii_case("deflate") {
+                deflate = true;
+            }
+        }
+
+        let inner = if gzip && ! deflate {
+            gzip::Decoder::new(transfer)
+                .map(|v| HttpContentInner::Gzip(BufReader::new(v)))
+        } else if deflate && ! gzip {
+            // some servers sends raw deflate stream without zlib header
+            match transfer.fill_buf() {
+                Ok(buf) if is_zlib(buf) => zlib::Decoder::new(transfer)
+                    .map(|v| HttpContentInner::Deflate(BufReader::new(v))),
+                Ok(_) => Ok(HttpContentInner::DeflateRaw(BufReader::new(deflate::Decoder::new(transfer)))),
+                Err(e) => Err(e),
+            }
+        } else {
+            Ok(HttpContentInner::Plain(transfer))
         };
+
+        match inner {
+            Ok(v) => {
+                self.inner = v;
+                Ok(())
+            }
+            Err(e) => {
+                // transport dropped with the decoder. connection is not usable
+                self.inner = HttpContentInner::Plain(HttpTransfer::default());
+                Err(e)
+            }
+        }
+    }
+
+    /// Removes content decoder
+    #[inline]
+    pub fn set_content_identity(&mut self) {
+        let transfer = self.take_transfer();
+        self.inner = HttpContentInner::Plain(transfer);
     }
 }
 
 
 impl Read for HttpContent {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        match self.inner {
+        match &mut self.inner {
             HttpContentInner::Plain(v) => v.read(buf),
-            HttpContentInner::Gzip(v) => v.read(buf),
+            HttpContentInner::Gzip(v) => {
+                let n = v.read(buf)?;
+                if n == 0 {
+                    drain(v.get_mut().as_inner_mut())?;
+                }
+                Ok(n)
+            }
+            HttpContentInner::Deflate(v) => {
+                let n = v.read(buf)?;
+                if n == 0 {
+                    drain(v.get_mut().as_inner_mut())?;
+                }
+                Ok(n)
+            }
+            HttpContentInner::DeflateRaw(v) => {
+                let n = v.read(buf)?;
+                if n == 0 {
+                    drain(v.get_mut().as_inner_mut())?;
+                }
+                Ok(n)
+            }
             HttpContentInner::None => unreachable!(),
         }
     }
@@ -77,17 +213,36 @@ impl Read for HttpContent {
 
 impl BufRead for HttpContent {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
-        match self.inner {
+        match &mut self.inner {
             HttpContentInner::Plain(v) => v.fill_buf(),
-            HttpContentInner::Gzip(v) => v.fill_buf(),
+            HttpContentInner::Gzip(v) => {
+                if v.fill_buf()?.is_empty() {
+                    drain(v.get_mut().as_inner_mut())?;
+                }
+                v.fill_buf()
+            }
+            HttpContentInner::Deflate(v) => {
+                if v.fill_buf()?.is_empty() {
+                    drain(v.get_mut().as_inner_mut())?;
+                }
+                v.fill_buf()
+            }
+            HttpContentInner::DeflateRaw(v) => {
+                if v.fill_buf()?.is_empty() {
+                    drain(v.get_mut().as_inner_mut())?;
+                }
+                v.fill_buf()
+            }
             HttpContentInner::None => unreachable!(),
         }
     }
 
     fn consume(&mut self, amt: usize) {
-        match self.inner {
+        match &mut self.inner {
             HttpContentInner::Plain(v) => v.consume(amt),
             HttpContentInner::Gzip(v) => v.consume(amt),
+            HttpContentInner::Deflate(v) => v.consume(amt),
+            HttpContentInner::DeflateRaw(v) => v.consume(amt),
             HttpContentInner::None => unreachable!(),
         }
     }
@@ -95,18 +250,13 @@ impl BufRead for HttpContent {
 
 
 impl Write for HttpContent {
+    #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        match self.inner {
-            HttpContentInner::Plain(v) => v.write(buf),
-            HttpContentInner::Gzip(v) => v.get_mut().as_inner_mut().write(buf),
-            HttpContentInner::None => unreachable!(),
-        }
+        self.as_transfer_mut().write(buf)
     }
 
+    #[inline]
     fn flush(&mut self) -> io::Result<()> {
-        match self.inner {
-            HttpContentInner::Plain(v) => v.flush(),
-            HttpContentInner::Gzip(v) => v.get_mut().as_inner_mut().flush(),
-            HttpContentInner::None => unreachable!(),
-        }
+        self.as_transfer_mut().flush()
+    }
 }
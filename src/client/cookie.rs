@@ -0,0 +1,630 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    fs,
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        BufWriter,
+        Write,
+    },
+    net::IpAddr,
+    path::Path,
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+    },
+    time::{
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use crate::Url;
+
+
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+
+/// Common second-level registry domains.
+/// It is a partial stand-in for the Public Suffix List (https://publicsuffix.org),
+/// other suffixes, e.g. `github.io`, are not recognized
+const REGISTRY_SUFFIXES: &[&str] = &[
+    "ac.uk", "co.uk", "gov.uk", "ltd.uk", "me.uk", "net.uk", "org.uk", "plc.uk",
+    "com.au", "net.au", "org.au", "edu.au", "gov.au",
+    "co.jp", "ne.jp", "or.jp", "ac.jp", "go.jp",
+    "co.kr", "or.kr", "co.nz", "net.nz", "org.nz", "co.za", "org.za",
+    "co.in", "net.in", "org.in", "com.br", "net.br", "org.br",
+    "com.cn", "net.cn", "org.cn", "com.hk", "com.tw", "com.sg",
+    "com.mx", "com.ar", "com.tr", "com.ua", "com.ru", "com.pl",
+];
+
+
+/// Returns current time in seconds since UNIX epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|v| v.as_secs())
+        .unwrap_or(0)
+}
+
+
+/// Number of days since UNIX epoch for the civil date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+
+/// Parses leading 1..=max digits. Returns None if token has no digits
+fn parse_digits(token: &str, min: usize, max: usize) -> Option<(i64, &str)> {
+    let len = token.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if len < min || len > max {
+        return None
+    }
+    Some((token[.. len].parse().ok()?, &token[len ..]))
+}
+
+
+/// Parses cookie date (RFC 6265 5.1.1).
+/// Also suitable for HTTP-date in other headers.
+/// Returns time in seconds since UNIX epoch
+pub fn parse_date(value: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun",
+        "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let is_delimiter = |c: char| matches!(c,
+        '\x09' | '\x20' ..= '\x2F' | '\x3B' ..= '\x40' | '\x5B' ..= '\x60' | '\x7B' ..= '\x7E');
+
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+
+    for token in value.split(is_delimiter).filter(|v| ! v.is_empty()) {
+        if time.is_none() {
+            let hms = parse_digits(token, 1, 2)
+                .and_then(|(h, rest)| parse_digits(rest.strip_prefix(':')?, 1, 2).map(|(m, rest)| (h, m, rest)))
+                .and_then(|(h, m, rest)| parse_digits(rest.strip_prefix(':')?, 1, 2).map(|(s, _)| (h, m, s)));
+            if hms.is_some() {
+                time = hms;
+                continue
+            }
+        }
+
+        if day.is_none() {
+            if let Some((v, _)) = parse_digits(token, 1, 2) {
+                day = Some(v);
+                continue
+            }
+        }
+
+        if month.is_none() {
+            // token could be not ASCII
+            let prefix = token.get(.. 3).map(str::to_ascii_lowercase);
+            if let Some(v) = prefix.and_then(|p| MONTHS.iter().position(|m| *m == p)) {
+                month = Some(v as i64 + 1);
+                continue
+            }
+        }
+
+        if year.is_none() {
+            if let Some((v, _)) = parse_digits(token, 2, 4) {
+                year = Some(v);
+                continue
+            }
+        }
+    }
+
+    let (hour, minute, second) = time?;
+    let day = day?;
+    let month = month?;
+    let year = match year? {
+        v @ 70 ..= 99 => v + 1900,
+        v @ 0 ..= 69 => v + 2000,
+        v => v,
+    };
+
+    if ! (1 ..= 31).contains(&day) || year < 1601 || hour > 23 || minute > 59 || second > 59 {
+        return None
+    }
+
+    let days = days_from_civil(year, month, day);
+    let ts = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    Some(ts.max(0) as u64)
+}
+
+
+/// Checks that host domain-matches to the cookie domain
+fn domain_match(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true
+    }
+
+    host.len() > domain.len() &&
+        host.ends_with(domain) &&
+        host.as_bytes()[host.len() - domain.len() - 1] == b'.' &&
+        host.parse::<IpAddr>().is_err()
+}
+
+
+/// Checks that domain is a top-level domain or a known registry domain,
+/// e.g. `com` or `co.uk`. It is not a complete public suffix check
+fn is_registry_suffix(domain: &str) -> bool {
+    ! domain.contains('.') || REGISTRY_SUFFIXES.contains(&domain)
+}
+
+
+/// Checks that request path path-matches to the cookie path
+fn path_match(path: &str, cookie_path: &str) -> bool {
+    if path == cookie_path {
+        return true
+    }
+
+    path.starts_with(cookie_path) && (
+        cookie_path.ends_with('/') ||
+        path.as_bytes()[cookie_path.len()] == b'/'
+    )
+}
+
+
+/// Default cookie path - directory of the request path
+fn default_path(path: &str) -> &str {
+    if ! path.starts_with('/') {
+        return "/"
+    }
+
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(v) => &path[.. v],
+    }
+}
+
+
+/// Stored cookie
+#[derive(Debug, Clone)]
+struct HttpCookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    /// Expiration time in seconds since UNIX epoch. None for session cookie
+    expires: Option<u64>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<String>,
+    /// Creation order
+    seq: u64,
+}
+
+
+impl HttpCookie {
+    /// Parses Set-Cookie value received from the url (RFC 6265 5.2)
+    fn parse(url: &Url, value: &str) -> Option<Self> {
+        let mut attrs = value.split(';');
+
+        let pair = attrs.next()?;
+        let skip = pair.find('=')?;
+        let name = pair[.. skip].trim();
+        if name.is_empty() {
+            return None
+        }
+
+        let host = url.get_host().to_ascii_lowercase();
+
+        let mut cookie = HttpCookie {
+            name: name.to_owned(),
+            value: pair[skip + 1 ..].trim().to_owned(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url.get_path()).to_owned(),
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+            seq: 0,
+        };
+
+        let mut max_age = None;
+        let mut expires = None;
+
+        for attr in attrs {
+            let (key, value) = match attr.find('=') {
+                Some(v) => (attr[.. v].trim(), attr[v + 1 ..].trim()),
+                None => (attr.trim(), ""),
+            };
+
+            if key.eq_ignore_ascii_case("expires") {
+                expires = parse_date(value).or(expires);
+            } else if key.eq_ignore_ascii_case("max-age") {
+                if let Ok(v) = value.parse::<i64>() {
+                    max_age = Some(if v > 0 { now() + v as u64 } else { 0 });
+                }
+            } else if key.eq_ignore_ascii_case("domain") {
+                let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                if ! domain.is_empty() {
+                    if ! domain_match(&host, &domain) {
+                        return None
+                    }
+                    // registry domain allowed only for the host itself (RFC 6265 5.3)
+                    if ! is_registry_suffix(&domain) {
+                        cookie.domain = domain;
+                        cookie.host_only = false;
+                    } else if domain != host {
+                        return None
+                    }
+                }
+            } else if key.eq_ignore_ascii_case("path") {
+                if value.starts_with('/') {
+                    cookie.path = value.to_owned();
+                }
+            } else if key.eq_ignore_ascii_case("secure") {
+                cookie.secure = true;
+            } else if key.eq_ignore_ascii_case("httponly") {
+                cookie.http_only = true;
+            } else if key.eq_ignore_ascii_case("samesite") {
+                cookie.same_site = Some(value.to_owned());
+            }
+        }
+
+        // Max-Age has precedence over Expires
+        cookie.expires = max_age.or(expires);
+
+        Some(cookie)
+    }
+
+    #[inline]
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.map(|v| v <= now).unwrap_or(false)
+    }
+
+    fn is_match(&self, url: &Url, now: u64) -> bool {
+        let host = url.get_host().to_ascii_lowercase();
+
+        let domain = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(&host, &self.domain)
+        };
+
+        let path = match url.get_path() {
+            "" => "/",
+            v => v,
+        };
+
+        domain &&
+            path_match(path, &self.path) &&
+            (! self.secure || url.get_scheme() == "https") &&
+            ! self.is_expired(now)
+    }
+
+    /// Parses line in the Netscape cookie file format
+    fn load(line: &str) -> Option<Self> {
+        let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(v) => (v, true),
+            None if line.starts_with('#') => return None,
+            None => (line, false),
+        };
+
+        let mut fields = line.split('\t');
+        let domain = fields.next()?;
+        let subdomains = fields.next()?;
+        let path = fields.next()?;
+        let secure = fields.next()?;
+        let expires: u64 = fields.next()?.parse().ok()?;
+        let name = fields.next()?;
+        let value = fields.next().unwrap_or("");
+
+        let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+        let host_only = ! subdomains.eq_ignore_ascii_case("TRUE") || is_registry_suffix(&domain);
+
+        Some(HttpCookie {
+            name: name.to_owned(),
+            value: value.to_owned(),
+            domain,
+            host_only,
+            path: path.to_owned(),
+            expires: if expires == 0 { None } else { Some(expires) },
+            secure: secure.eq_ignore_ascii_case("TRUE"),
+            http_only,
+            same_site: None,
+            seq: 0,
+        })
+    }
+
+    /// Writes cookie in the Netscape cookie file format
+    fn save<W: Write>(&self, dst: &mut W) -> io::Result<()> {
+        let bool_str = |v: bool| if v { "TRUE" } else { "FALSE" };
+
+        writeln!(dst, "{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            if self.http_only { HTTP_ONLY_PREFIX } else { "" },
+            if self.host_only { "" } else { "." },
+            self.domain,
+            bool_str(! self.host_only),
+            self.path,
+            bool_str(self.secure),
+            self.expires.unwrap_or(0),
+            self.name,
+            self.value)
+    }
+}
+
+
+#[derive(Debug, Default)]
+struct HttpCookieJarInner {
+    cookies: Vec<HttpCookie>,
+    seq: u64,
+}
+
+
+impl HttpCookieJarInner {
+    fn insert(&mut self, mut cookie: HttpCookie) {
+        let now = now();
+        self.cookies.retain(|v| ! v.is_expired(now));
+
+        let position = self.cookies.iter().position(|v| {
+            v.name == cookie.name &&
+            v.domain == cookie.domain &&
+            v.path == cookie.path
+        });
+
+        if cookie.is_expired(now) {
+            // expired cookie removes previous one
+            if let Some(v) = position {
+                self.cookies.remove(v);
+            }
+            return
+        }
+
+        match position {
+            Some(v) => {
+                cookie.seq = self.cookies[v].seq;
+                self.cookies[v] = cookie;
+            }
+            None => {
+                self.seq += 1;
+                cookie.seq = self.seq;
+                self.cookies.push(cookie);
+            }
+        }
+    }
+}
+
+
+/// Cookie storage (RFC 6265) shared between clients
+///
+/// Stores cookies from Set-Cookie response headers
+/// and sends matching cookies with next requests and redirects.
+/// Supported attributes: Domain, Path, Expires, Max-Age, Secure, HttpOnly, and SameSite.
+/// SameSite is stored but not applied, client has no site context.
+///
+/// Usage:
+///
+/// ```no_run
+/// use http::{
+///     HttpClient,
+///     HttpCookieJar,
+/// };
+///
+/// let jar = HttpCookieJar::new();
+/// jar.load("/tmp/cookies.txt").ok();
+///
+/// let mut client = HttpClient::new("http://camera.local/login").unwrap();
+/// client.set_cookie_jar(&jar);
+/// client.get().unwrap();
+///
+/// jar.save("/tmp/cookies.txt").unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct HttpCookieJar {
+    inner: Arc<Mutex<HttpCookieJarInner>>,
+}
+
+
+impl HttpCookieJar {
+    /// Allocates new empty cookie jar
+    #[inline]
+    pub fn new() -> Self { HttpCookieJar::default() }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, HttpCookieJarInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Stores cookie from Set-Cookie header value received from the url.
+    /// Invalid cookies are ignored
+    pub fn set_cookie(&self, url: &Url, value: &str) {
+        if let Some(cookie) = HttpCookie::parse(url, value) {
+            self.lock().insert(cookie);
+        }
+    }
+
+    /// Returns value for Cookie header with all cookies matching url
+    #[inline]
+    pub fn get_cookie(&self, url: &Url) -> Option<String> { self.get_cookie_except(url, "") }
+
+    /// Returns value for Cookie header with cookies matching url
+    /// except cookies with names defined in the `header` value
+    pub (crate) fn get_cookie_except(&self, url: &Url, header: &str) -> Option<String> {
+        let now = now();
+        let inner = self.lock();
+
+        let names: Vec<&str> = header
+            .split(';')
+            .filter_map(|v| v.split('=').next())
+            .map(|v| v.trim())
+            .filter(|v| ! v.is_empty())
+            .collect();
+
+        let mut list: Vec<&HttpCookie> = inner.cookies
+            .iter()
+            .filter(|v| v.is_match(url, now) && ! names.contains(&v.name.as_str()))
+            .collect();
+
+        if list.is_empty() {
+            return None
+        }
+
+        // longer paths first, then earlier created
+        list.sort_by(|a, b| b.path.len().cmp(&a.path.len()).then(a.seq.cmp(&b.seq)));
+
+        let mut result = String::new();
+        for cookie in list {
+            if ! result.is_empty() {
+                result.push_str("; ");
+            }
+            result.push_str(&cookie.name);
+            result.push('=');
+            result.push_str(&cookie.value);
+        }
+
+        Some(result)
+    }
+
+    /// Returns number of stored cookies
+    #[inline]
+    pub fn len(&self) -> usize { self.lock().cookies.len() }
+
+    /// Returns true if jar has no cookies
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.lock().cookies.is_empty() }
+
+    /// Removes all cookies
+    #[inline]
+    pub fn clear(&self) { self.lock().cookies.clear() }
+
+    /// Loads cookies from file in the Netscape format (compatible with curl).
+    /// Expired cookies are skipped
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = fs::File::open(path)?;
+        let now = now();
+        let mut inner = self.lock();
+
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            let line = line.trim_end_matches('\r');
+            if let Some(cookie) = HttpCookie::load(line) {
+                if ! cookie.is_expired(now) {
+                    inner.insert(cookie);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Saves cookies into file in the Netscape format (compatible with curl).
+    /// Session cookies saved with zero expiration time
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let now = now();
+        let inner = self.lock();
+
+        let mut dst = BufWriter::new(fs::File::create(path)?);
+        writeln!(dst, "# Netscape HTTP Cookie File")?;
+        for cookie in inner.cookies.iter().filter(|v| ! v.is_expired(now)) {
+            cookie.save(&mut dst)?;
+        }
+        dst.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("Wed, 21 Oct 2015 07:28:00 GMT"), Some(1_445_412_480));
+        assert_eq!(parse_date("Wednesday, 21-Oct-15 07:28:00 GMT"), Some(1_445_412_480));
+        assert_eq!(parse_date("Thu Jan  1 00:00:00 1970"), Some(0));
+        assert_eq!(parse_date("21 Oct 2015"), None);
+        assert_eq!(parse_date("32 Oct 2015 07:28:00"), None);
+        assert_eq!(parse_date("éé 10:00:00 2030"), None);
+        assert_eq!(parse_date("21 Oé 2015 07:28:00"), None);
+    }
+
+    #[test]
+    fn test_parse_cookie() {
+        let url = Url::new("http://www.example.com/app/login").unwrap();
+
+        let cookie = HttpCookie::parse(&url, "sid=abc; Path=/; Domain=.example.com; HttpOnly; SameSite=Lax").unwrap();
+        assert_eq!(cookie.name, "sid");
+        assert_eq!(cookie.value, "abc");
+        assert_eq!(cookie.domain, "example.com");
+        assert!(! cookie.host_only);
+        assert_eq!(cookie.path, "/");
+        assert!(cookie.http_only);
+        assert_eq!(cookie.same_site.as_deref(), Some("Lax"));
+
+        let cookie = HttpCookie::parse(&url, "lang=en").unwrap();
+        assert_eq!(cookie.domain, "www.example.com");
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/app");
+        assert_eq!(cookie.expires, None);
+
+        let cookie = HttpCookie::parse(&url, "a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=60").unwrap();
+        assert!(cookie.expires.unwrap() > 1_445_412_480);
+
+        // invalid date ignored
+        let cookie = HttpCookie::parse(&url, "a=1; Expires=éé 10:00:00 2030").unwrap();
+        assert_eq!(cookie.expires, None);
+
+        // foreign domain
+        assert!(HttpCookie::parse(&url, "a=1; Domain=example.org").is_none());
+
+        // top-level and registry domains
+        assert!(HttpCookie::parse(&url, "a=1; Domain=com").is_none());
+        let url_uk = Url::new("http://www.example.co.uk/").unwrap();
+        assert!(HttpCookie::parse(&url_uk, "a=1; Domain=co.uk").is_none());
+        assert!(! HttpCookie::parse(&url_uk, "a=1; Domain=example.co.uk").unwrap().host_only);
+        let url_local = Url::new("http://localhost/").unwrap();
+        assert!(HttpCookie::parse(&url_local, "a=1; Domain=localhost").unwrap().host_only);
+        assert!(HttpCookie::parse(&url, "=1").is_none());
+    }
+
+    #[test]
+    fn test_match() {
+        assert!(domain_match("www.example.com", "example.com"));
+        assert!(! domain_match("www.badexample.com", "example.com"));
+        assert!(! domain_match("1.2.3.4", "2.3.4"));
+
+        assert!(path_match("/app/login", "/app"));
+        assert!(path_match("/app/login", "/app/"));
+        assert!(! path_match("/application", "/app"));
+    }
+
+    #[test]
+    fn test_jar() {
+        let jar = HttpCookieJar::new();
+        let url = Url::new("http://example.com/app/").unwrap();
+        jar.set_cookie(&url, "a=1; Path=/");
+        jar.set_cookie(&url, "b=2");
+        jar.set_cookie(&url, "c=3; Secure");
+        assert_eq!(jar.len(), 3);
+
+        assert_eq!(jar.get_cookie(&url).as_deref(), Some("b=2; a=1"));
+
+        let url = Url::new("https://example.com/").unwrap();
+        assert_eq!(jar.get_cookie(&url).as_deref(), Some("a=1"));
+
+        // removes cookie
+        jar.set_cookie(&url, "a=; Path=/; Max-Age=0");
+        assert_eq!(jar.get_cookie(&url), None);
+        assert_eq!(jar.len(), 2);
+    }
+}
@@ -10,27 +10,92 @@ use std::{
         self,
         BufRead,
         Read,
+        Seek,
+        SeekFrom,
         Write,
     },
+    mem,
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
 };
 
 use crate::{
+    Header,
     HttpVersion,
+    Multipart,
     Request,
     RequestError,
     Response,
     ResponseError,
+    Url,
     UrlError,
     UrlSetter,
 };
 
-mod auth;
-use self::auth::http_auth;
+pub (crate) mod auth;
+use self::auth::{
+    HttpAuth,
+    HttpAuthError,
+    is_stale,
+    http_auth,
+    proxy_auth,
+};
+pub use self::auth::{
+    Authenticator,
+    BasicAuth,
+    BearerAuth,
+    DigestAuth,
+    HttpAuthContext,
+    HttpChallenge,
+};
 
 pub (crate) mod transfer;
 use self::transfer::{
-    HttpTransfer,
     HttpTransferError,
+    stream::{
+        HttpStreamError,
+        HttpTimeout,
+        HttpProxy,
+        HttpProxyKind,
+    },
+};
+pub use self::transfer::stream::{
+    HttpTls,
+    HttpTlsConfig,
+    HttpTlsVersion,
+};
+
+pub (crate) mod pool;
+use self::pool::HttpPoolKey;
+pub use self::pool::HttpPool;
+
+mod cookie;
+pub use self::cookie::HttpCookieJar;
+
+mod retry;
+use self::retry::{
+    HttpRetry,
+    is_idempotent,
+    is_retryable,
+    parse_retry_after,
+};
+
+mod range;
+use self::range::{
+    ContentRange,
+    get_validator,
+};
+
+mod rtsp;
+use self::rtsp::RtspHandler;
+
+mod content;
+use self::content::{
+    HttpContent,
+    ACCEPT_ENCODING,
 };
 
 
@@ -47,21 +112,105 @@ pub enum HttpClientError {
     HttpTransfer(HttpTransferError),
     #[error_from]
     Url(UrlError),
+    #[error_from]
+    Tls(openssl::error::ErrorStack),
+    #[error_from]
+    Auth(HttpAuthError),
     #[error_kind("invalid protocol")]
     InvalidProtocol,
     #[error_kind("redirect location not defined")]
     InvalidRedirectLocation,
+    #[error_kind("redirect loop")]
+    RedirectLoop,
+    #[error_kind("too many redirects")]
+    TooManyRedirects,
+    #[error_kind("invalid content range")]
+    InvalidContentRange,
     #[error_kind("request failed: {} {}", 0, 1)]
     RequestFailed(usize, String),
+    #[error_kind("timeout")]
+    Timeout,
 }
 
 
 pub type Result<T> = std::result::Result<T, HttpClientError>;
 
 
+/// Converts socket timeout into HttpClientError::Timeout
+fn check_timeout(e: HttpClientError) -> HttpClientError {
+    let inner = match &e {
+        HttpClientError::Io(e) => e,
+        HttpClientError::Request(RequestError::Io(e)) => e,
+        HttpClientError::Response(ResponseError::Io(e)) => e,
+        HttpClientError::HttpTransfer(HttpTransferError::Io(e)) => e,
+        HttpClientError::HttpTransfer(HttpTransferError::HttpStream(HttpStreamError::Io(e))) => e,
+        _ => return e,
+    };
+
+    match inner.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => HttpClientError::Timeout,
+        _ => e,
+    }
+}
+
+
+/// Returns port from url or default port for the scheme
+fn get_port(url: &Url) -> u16 {
+    match (url.get_port(), url.get_scheme()) {
+        (0, "http") => 80,
+        (0, "https") => 443,
+        (0, "rtsp") => 554,
+        (port, _) => port,
+    }
+}
+
+
+/// Joins values of the Cookie header
+fn join_cookie(a: &str, b: &str) -> String {
+    match (a.is_empty(), b.is_empty()) {
+        (true, _) => b.to_owned(),
+        (false, true) => a.to_owned(),
+        (false, false) => format!("{}; {}", a, b),
+    }
+}
+
+
+/// Compares scheme, host, and port of the urls
+fn is_same_origin(a: &Url, b: &Url) -> bool {
+    a.get_scheme() == b.get_scheme() &&
+    a.get_host().eq_ignore_ascii_case(b.get_host()) &&
+    get_port(a) == get_port(b)
+}
+
+
 pub const USER_AGENT: &str = concat!("libhttp/", env!("CARGO_PKG_VERSION"));
 
 
+const DEFAULT_MAX_REDIRECTS: usize = 3;
+
+
+/// Maximum number of resume attempts in `download()` without received data
+const MAX_RESUME_ATTEMPTS: usize = 3;
+
+
+/// Proxy configuration
+#[derive(Debug)]
+enum HttpProxyConfig {
+    /// Proxy defined by environment variables
+    Env,
+    /// Proxy defined by `set_proxy()`
+    Proxy(HttpProxy),
+    /// Proxy disabled
+    None,
+}
+
+
+impl Default for HttpProxyConfig {
+    #[inline]
+    fn default() -> Self { HttpProxyConfig::Env }
+}
+
+
 /// HTTP client
 ///
 /// Basic Usage:
@@ -102,8 +251,50 @@ pub struct HttpClient {
     pub request: Request,
     /// received HTTP response
     pub response: Response,
-    /// HTTP stream
-    transfer: HttpTransfer,
+    /// HTTP stream with content decoder
+    content: HttpContent,
+    /// socket timeouts
+    timeout: HttpTimeout,
+    /// overall timeout for get()
+    total_timeout: Duration,
+    deadline: Option<Instant>,
+    /// proxy server
+    proxy: HttpProxyConfig,
+    /// TLS context
+    tls: Option<HttpTls>,
+    /// request body for get()
+    body: Vec<u8>,
+    /// maximum number of redirects for get()
+    max_redirects: Option<usize>,
+    /// visited urls
+    redirects: Vec<String>,
+    /// url, method, and cookies of the requests in the redirect chain
+    redirect_requests: Vec<(String, String, String)>,
+    /// url of the first request in the redirect chain
+    redirect_origin: Option<Url>,
+    /// Cookie and Authorization headers of the first request,
+    /// removed while redirect chain is on another origin
+    redirect_headers: (Option<String>, Option<String>),
+    /// next request continues redirect chain
+    redirect_pending: bool,
+    /// cookie storage
+    cookies: Option<HttpCookieJar>,
+    /// cookies from the jar appended to the Cookie header of the last request
+    jar_cookie: String,
+    /// credentials defined with `set_credentials()`
+    credentials: Option<(String, String)>,
+    /// authentication on the origin server
+    auth: HttpAuth,
+    /// redirect chain is on another origin
+    cross_origin: bool,
+    /// retry policy for get()
+    retry: HttpRetry,
+    /// sequence number of the last RTSP request
+    cseq: usize,
+    /// handler for the requests from RTSP server
+    rtsp_handler: RtspHandler,
+    /// authentication on the HTTP proxy server
+    proxy_auth: HttpAuth,
 }
 
 
@@ -121,55 +312,348 @@ impl HttpClient {
         self.request.header.clear();
         self.request.header.set("Host", self.request.url.as_address());
         self.request.header.set("User-Agent", USER_AGENT);
+        self.request.header.set("Accept-Encoding", ACCEPT_ENCODING);
         Ok(())
     }
 
     /// Close connection
     /// Method should not used manually
     #[inline]
-    pub fn close(&mut self) { self.transfer.close() }
+    pub fn close(&mut self) { self.content.as_transfer_mut().close() }
+
+    /// Sets shared connection pool.
+    /// Idle keep-alive connections returns into the pool after end of response
+    /// and could be used by any client with the same pool
+    #[inline]
+    pub fn set_pool(&mut self, pool: &HttpPool) {
+        self.content.as_transfer_mut().set_pool(pool.clone())
+    }
+
+    /// Sets cookie jar.
+    /// Cookies from the responses stores into the jar
+    /// and sends with next requests to the matching hosts.
+    /// Cookie header defined in the request is sent with the jar cookies
+    #[inline]
+    pub fn set_cookie_jar(&mut self, jar: &HttpCookieJar) { self.cookies = Some(jar.clone()) }
+
+    /// Sets username and password for the origin server.
+    /// Used instead of credentials from the url, so they are not exposed in logs.
+    /// Credentials are not sent after redirect to another origin
+    pub fn set_credentials(&mut self, username: &str, password: &str) {
+        self.credentials = Some((username.to_owned(), password.to_owned()))
+    }
+
+    /// Appends authenticator for the origin server.
+    /// Authenticators are checked in order of addition
+    /// before Digest and Basic authentication with credentials from the url.
+    /// Could be used for Bearer tokens or custom schemes
+    #[inline]
+    pub fn add_authenticator<A: Authenticator + 'static>(&mut self, auth: A) {
+        self.auth.push(Box::new(auth))
+    }
+
+    /// Sets proxy server: `scheme://[username:password@]host[:port]`
+    ///
+    /// Supported schemes:
+    ///
+    /// - `http` - plain HTTP requests are sent to the proxy with absolute URI.
+    ///   HTTPS and other protocols are tunneled with CONNECT method
+    /// - `socks5` - all connections are tunneled through SOCKS5 proxy.
+    ///   Host name resolves on the proxy side
+    ///
+    /// Default port is 1080. Empty value disables proxy.
+    /// By default proxy defined by environment variables:
+    /// `http_proxy`, `https_proxy`, and `no_proxy`
+    pub fn set_proxy<R: UrlSetter>(&mut self, url: R) -> Result<()> {
+        let url = Url::new(url)?;
+        if url.get_host().is_empty() {
+            self.proxy = HttpProxyConfig::None;
+        } else {
+            let proxy = HttpProxy::new(&url).ok_or(HttpClientError::InvalidProtocol)?;
+            self.proxy = HttpProxyConfig::Proxy(proxy);
+        }
+        Ok(())
+    }
+
+    /// Sets TLS configuration for HTTPS connections:
+    /// trusted CA certificates, client certificate, protocol version, and ciphers.
+    /// By default used system CA certificates.
+    /// Creates new TLS context, pooled connections and TLS sessions
+    /// are not shared with other clients. See `set_tls_context()`
+    pub fn set_tls(&mut self, config: &HttpTlsConfig) -> Result<()> {
+        self.tls = Some(HttpTls::new(config)?);
+        Ok(())
+    }
+
+    /// Sets prepared TLS context for HTTPS connections.
+    /// Clients with the same context share pooled connections and TLS sessions
+    #[inline]
+    pub fn set_tls_context(&mut self, tls: &HttpTls) { self.tls = Some(tls.clone()) }
+
+    /// Sets request body for `get()`. Defines Content-Length header.
+    /// Body sends again on authentication and 307/308 redirects
+    pub fn set_body<B: Into<Vec<u8>>>(&mut self, body: B) {
+        self.body = body.into();
+        self.request.header.set("Content-Length", self.body.len());
+    }
+
+    /// Sets maximum number of redirects for `get()`. Default: 3
+    #[inline]
+    pub fn set_max_redirects(&mut self, value: usize) { self.max_redirects = Some(value) }
+
+    /// Sets maximum number of retries for idempotent requests in `get()`.
+    /// Request repeats on connection errors, timeouts,
+    /// and 502, 503, 504 responses. Default: 0
+    #[inline]
+    pub fn set_max_retries(&mut self, value: usize) { self.retry.max_retries = value }
+
+    /// Sets delay before first retry and maximum delay between retries.
+    /// Delay doubles on each retry with random jitter.
+    /// Delay from the Retry-After header is used if it does not exceed maximum delay,
+    /// otherwise request fails without retry.
+    /// Default: 200ms and 30s
+    pub fn set_retry_delay(&mut self, delay: Duration, max_delay: Duration) {
+        self.retry.delay = delay;
+        self.retry.max_delay = max_delay;
+    }
+
+    /// Returns redirect chain of the last `get()`:
+    /// requested url and all followed locations
+    #[inline]
+    pub fn get_redirects(&self) -> &[String] { &self.redirects }
+
+    /// Returns sequence number of the last RTSP request.
+    /// CSeq header defines automatically for each RTSP request
+    #[inline]
+    pub fn get_cseq(&self) -> usize { self.cseq }
+
+    /// Returns true if TLS session of the current connection was resumed
+    /// with abbreviated handshake.
+    /// Sessions are cached by host and port in the TLS context
+    #[inline]
+    pub fn is_session_reused(&self) -> bool { self.content.as_transfer().is_session_reused() }
+
+    /// Sets timeout for TCP connection
+    /// Zero value disables timeout. Default: 3s
+    #[inline]
+    pub fn set_connect_timeout(&mut self, timeout: Duration) { self.timeout.connect = timeout }
+
+    /// Sets timeout for each read operation
+    /// Zero value disables timeout. Default: 3s
+    #[inline]
+    pub fn set_read_timeout(&mut self, timeout: Duration) { self.timeout.read = timeout }
+
+    /// Sets timeout for each write operation
+    /// Zero value disables timeout. Default: 3s
+    #[inline]
+    pub fn set_write_timeout(&mut self, timeout: Duration) { self.timeout.write = timeout }
+
+    /// Sets overall timeout for `get()` including redirects and authentication.
+    /// Content reading after `get()` limited by socket timeouts only.
+    /// Zero value disables timeout. Default: 0
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: Duration) { self.total_timeout = timeout }
+
+    /// Returns socket timeouts limited by the overall deadline
+    fn get_timeout(&self) -> Result<HttpTimeout> {
+        match self.deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(HttpClientError::Timeout);
+                }
+                Ok(self.timeout.limit(deadline - now))
+            }
+            None => Ok(self.timeout),
+        }
+    }
 
     /// Connects to destination host, sends request line and headers
-    /// Prepares HTTP stream for writing data
+    /// Prepares HTTP stream for writing data.
+    /// Idempotent request repeats on new connection
+    /// if reused connection was closed by server
     pub fn send(&mut self) -> Result<()> {
-        let mut tls = false;
-        let mut port = self.request.url.get_port();
+        // redirect() prepares next request in the same chain
+        if self.redirect_pending {
+            self.redirect_pending = false;
+        } else {
+            self.reset_redirects();
+        }
+
+        let mut result = self.io_send();
+        if result.is_err() && self.is_stale_connection() {
+            self.close();
+            result = self.io_send();
+        }
+
+        result.map_err(check_timeout)
+    }
+
+    fn io_send(&mut self) -> Result<()> {
+        let port = get_port(&self.request.url);
 
         match self.request.url.get_scheme() {
-            "http" => {
-                if port == 0 {
-                    port = 80;
-                }
-            },
-            "https" => {
-                if port == 0 {
-                    port = 443;
-                }
-                tls = true;
-            }
+            "http" | "https" => {}
             "rtsp" => {
-                if port == 0 {
-                    port = 554;
-                }
                 self.request.set_version(HttpVersion::RTSP10);
+                self.cseq += 1;
+                self.request.header.set("CSeq", self.cseq);
             }
             _ => return Err(HttpClientError::InvalidProtocol)
         };
 
+        let scheme = self.request.url.get_scheme();
         let host = self.request.url.get_host();
 
-        self.transfer.connect(tls, host, port)?;
-        self.request.send(&mut self.transfer)?;
-        self.transfer.flush()?;
+        let proxy = match &self.proxy {
+            HttpProxyConfig::Env => HttpProxy::from_env(scheme, host),
+            HttpProxyConfig::Proxy(proxy) => Some(proxy.clone()),
+            HttpProxyConfig::None => None,
+        };
+
+        let origin = match proxy {
+            Some(proxy) if proxy.get_kind() == HttpProxyKind::Http && scheme == "http" => {
+                // plain HTTP request to the proxy server with absolute URI
+                self.request.absolute_form = true;
+                self.proxy_auth.set_credentials(proxy.get_username(), proxy.get_password());
+                let uri = self.request.url.as_url().to_string();
+                proxy_auth(&mut self.proxy_auth, &mut self.request, &self.response, &uri)?;
+                HttpPoolKey::new("http", proxy.get_host(), proxy.get_port(), None)
+            }
+            proxy => {
+                self.request.absolute_form = false;
+                self.request.header.remove("proxy-authorization");
+                let mut origin = HttpPoolKey::new(scheme, host, port, proxy);
+                if origin.is_tls() {
+                    origin.set_tls(self.tls.clone());
+                }
+                origin
+            }
+        };
+
+        self.set_cookie_header();
+
+        let timeout = self.get_timeout()?;
+
+        self.content.set_content_identity();
+        let transfer = self.content.as_transfer_mut();
+        transfer.connect(&origin, timeout)?;
+        self.request.send(transfer)?;
+        transfer.flush()?;
+
+        if let Some(encoding) = self.request.header.get("transfer-encoding") {
+            if encoding.split(',').any(|v| v.trim().eq_ignore_ascii_case("chunked")) {
+                transfer.set_write_chunked();
+            }
+        }
 
         Ok(())
     }
 
+    /// Sends request with multipart body and receives response.
+    /// Defines Content-Type and Content-Length headers.
+    /// If size of any part is unknown, body sends with chunked transfer encoding.
+    /// Request method should be defined before, usually it is POST
+    pub fn send_multipart(&mut self, form: Multipart) -> Result<()> {
+        self.clear_body();
+        self.request.header.set("Content-Type", form.get_content_type());
+        match form.get_length() {
+            Some(len) => self.request.header.set("Content-Length", len),
+            None => self.request.header.set("Transfer-Encoding", "chunked"),
+        }
+
+        self.send()?;
+        form.send(self).map_err(|e| check_timeout(e.into()))?;
+        self.receive()
+    }
+
+    /// Completes request body with last chunk
+    /// if request has header `Transfer-Encoding: chunked`.
+    /// Called by `receive()` if not called manually
+    #[inline]
+    pub fn finish(&mut self) -> Result<()> {
+        self.content.as_transfer_mut().finish(None)?;
+        Ok(())
+    }
+
+    /// Completes request body with last chunk and trailer
+    /// if request has header `Transfer-Encoding: chunked`
+    #[inline]
+    pub fn finish_trailer(&mut self, trailer: &Header) -> Result<()> {
+        self.content.as_transfer_mut().finish(Some(trailer))?;
+        Ok(())
+    }
+
     /// Flushes writing buffer, receives response line and headers
-    /// Prepares HTTP stream for reading data
+    /// Prepares HTTP stream for reading data.
+    /// Idempotent request without body repeats on new connection
+    /// if reused connection was closed by server before response.
+    /// Request with body could not be repeated, because body
+    /// already written by caller
     pub fn receive(&mut self) -> Result<()> {
-        self.transfer.flush()?;
-        self.response.parse(&mut self.transfer)?;
+        let mut result = self.io_receive();
+
+        let failed = result.is_err() || self.response.get_code() == 0;
+        if failed && self.is_stale_connection() && ! self.has_request_body() {
+            self.close();
+            result = self.io_send().and_then(|_| self.io_receive());
+        }
+
+        result.map_err(check_timeout)
+    }
+
+    /// Returns true if idempotent request sent on reused connection
+    /// and nothing received from server
+    fn is_stale_connection(&self) -> bool {
+        let transfer = self.content.as_transfer();
+        is_idempotent(self.request.get_method()) &&
+            transfer.is_reused() &&
+            ! transfer.is_received()
+    }
+
+    /// Returns true if request has Content-Length or Transfer-Encoding
+    fn has_request_body(&self) -> bool {
+        self.request.header.get("transfer-encoding").is_some() ||
+            self.request.header.get("content-length")
+                .map(|v| v.trim() != "0")
+                .unwrap_or(false)
+    }
+
+    fn io_receive(&mut self) -> Result<()> {
+        let transfer = self.content.as_transfer_mut();
+        transfer.finish(None)?;
+        transfer.flush()?;
+
+        let rtsp = matches!(self.request.get_version(), HttpVersion::RTSP10 | HttpVersion::RTSP20);
+
+        loop {
+            if rtsp {
+                // interleaved frames and requests from server before response
+                while self.read_rtsp()? {}
+            }
+
+            self.response.parse(self.content.as_transfer_mut())?;
+
+            if rtsp && self.is_stale_response() {
+                // response for the previous request, e.g. after timeout
+                let len = self.response.header.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+                let transfer = self.content.as_transfer_mut();
+                transfer.set_content_length(len);
+                io::copy(transfer, &mut io::sink())?;
+                transfer.set_content_persist();
+                continue
+            }
+
+            break
+        }
+
+        let transfer = self.content.as_transfer_mut();
+
+        if let Some(jar) = &self.cookies {
+            for cookie in self.response.header.get_all("set-cookie") {
+                jar.set_cookie(&self.request.url, cookie);
+            }
+        }
 
         let code = self.response.get_code();
         let no_content = {
@@ -181,47 +665,144 @@ impl HttpClient {
 
         if let Some(connection) = self.response.header.get("connection") {
             if connection.eq_ignore_ascii_case("keep-alive") {
-                self.transfer.set_connection_keep_alive();
+                transfer.set_connection_keep_alive();
             } else {
-                self.transfer.set_connection_close();
+                transfer.set_connection_close();
             }
         } else if self.response.get_version() == HttpVersion::HTTP10 {
-            self.transfer.set_connection_close();
+            transfer.set_connection_close();
         } else {
-            self.transfer.set_connection_keep_alive();
+            transfer.set_connection_keep_alive();
         }
 
         if no_content {
-            self.transfer.set_content_length(0);
+            transfer.set_content_length(0);
             return Ok(());
         }
 
-        if let Some(_encoding) = self.response.header.get("content-encoding") {
-            // TODO:
+        self.set_transfer_encoding();
+
+        if let Some(encoding) = self.response.header.get("content-encoding") {
+            self.content.set_content_encoding(encoding)?;
         }
 
+        Ok(())
+    }
+
+    /// Defines how response body is delimited in the stream
+    fn set_transfer_encoding(&mut self) {
+        let transfer = self.content.as_transfer_mut();
+
         if let Some(len) = self.response.header.get("content-length") {
             let len = len.parse().unwrap_or(0);
-            self.transfer.set_content_length(len);
-            return Ok(());
+            transfer.set_content_length(len);
+            return;
+        }
+
+        // RTSP response without Content-Length has no body
+        match self.response.get_version() {
+            HttpVersion::RTSP10 | HttpVersion::RTSP20 => {
+                transfer.set_content_length(0);
+                return;
+            }
+            _ => {}
         }
 
         if let Some(encoding) = self.response.header.get("transfer-encoding") {
             for i in encoding.split(',').map(|v| v.trim()) {
                 if i.eq_ignore_ascii_case("chunked") {
-                    self.transfer.set_content_chunked();
-                    return Ok(());
+                    transfer.set_content_chunked();
+                    return;
                 }
             }
         }
 
-        if code == 200 {
-            self.transfer.set_content_persist();
+        if self.response.get_code() == 200 {
+            transfer.set_content_persist();
         } else {
-            self.transfer.set_content_length(0);
+            transfer.set_content_length(0);
         }
+    }
 
-        Ok(())
+    /// Sets handler for the RTSP interleaved channel.
+    /// Frames are received with `read_frame()` and before each RTSP response.
+    /// Frames for channels without handler are dropped
+    pub fn set_interleaved<F>(&mut self, channel: u8, handler: F)
+    where
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        self.content.as_transfer_mut().set_interleaved(channel, Box::new(handler))
+    }
+
+    /// Removes handler for the RTSP interleaved channel
+    #[inline]
+    pub fn remove_interleaved(&mut self, channel: u8) {
+        self.content.as_transfer_mut().remove_interleaved(channel)
+    }
+
+    /// Sets handler for the requests from RTSP server,
+    /// e.g. SET_PARAMETER, ANNOUNCE, or REDIRECT.
+    /// Handler receives request and request body,
+    /// and should send response with the same CSeq into the writer.
+    /// Requests are received with `read_frame()` and before each RTSP response.
+    /// Without handler OPTIONS is accepted, other requests are not implemented
+    ///
+    /// Usage:
+    ///
+    /// ```no_run
+    /// use http::{
+    ///     HttpClient,
+    ///     Response,
+    /// };
+    ///
+    /// let mut client = HttpClient::new("rtsp://camera.local/stream").unwrap();
+    /// client.set_rtsp_handler(|request, _body, writer| {
+    ///     let mut response = Response::default();
+    ///     response.set_version(request.get_version());
+    ///     response.set_code(200);
+    ///     response.set_reason("OK");
+    ///     response.header.set("CSeq", request.header.get("cseq").unwrap_or("0"));
+    ///     response.send(writer)
+    /// });
+    /// ```
+    pub fn set_rtsp_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&Request, &[u8], &mut dyn Write) -> std::result::Result<(), ResponseError> + Send + 'static,
+    {
+        self.rtsp_handler.set_handler(Box::new(handler))
+    }
+
+    /// Checks that RTSP response has CSeq of the previous request
+    fn is_stale_response(&self) -> bool {
+        match self.response.header.get("cseq").map(|v| v.trim().parse::<usize>()) {
+            Some(Ok(cseq)) => cseq < self.cseq,
+            _ => false,
+        }
+    }
+
+    /// Reads RTSP interleaved frame or request from server.
+    /// Returns false if next data is an RTSP response
+    fn read_rtsp(&mut self) -> Result<bool> {
+        let transfer = self.content.as_transfer_mut();
+        if transfer.read_frame()? {
+            return Ok(true)
+        }
+
+        if ! transfer.is_request()? {
+            return Ok(false)
+        }
+
+        transfer.set_content_persist();
+        self.rtsp_handler.process(transfer)?;
+        Ok(true)
+    }
+
+    /// Reads next RTSP interleaved frame and passes it to the channel handler,
+    /// or request from server and passes it to the handler defined with `set_rtsp_handler()`.
+    /// Returns false if next data in the stream is an RTSP response.
+    /// Response body should be completely read before
+    pub fn read_frame(&mut self) -> Result<bool> {
+        self.read_rtsp().map_err(check_timeout)
     }
 
     /// Reads response body from receiving buffer and stream
@@ -231,7 +812,97 @@ impl HttpClient {
         Ok(())
     }
 
+    /// Returns Cookie header value defined by user
+    /// without cookies appended from the jar
+    fn get_user_cookie(&self) -> Option<&str> {
+        let value = self.request.header.get("cookie")?;
+        let jar = self.jar_cookie.as_str();
+
+        if jar.is_empty() {
+            Some(value)
+        } else if value == jar {
+            None
+        } else {
+            Some(value.strip_suffix(jar).and_then(|v| v.strip_suffix("; ")).unwrap_or(value))
+        }
+    }
+
+    /// Appends cookies from the jar to the Cookie header defined by user.
+    /// User cookies take precedence over the jar cookies with the same name
+    fn set_cookie_header(&mut self) {
+        let jar = match &self.cookies {
+            Some(v) => v,
+            None => return,
+        };
+
+        let user = self.get_user_cookie().unwrap_or("").to_owned();
+        let cookie = jar.get_cookie_except(&self.request.url, &user).unwrap_or_default();
+        let value = join_cookie(&user, &cookie);
+        self.jar_cookie = cookie;
+
+        if value.is_empty() {
+            self.request.header.remove("cookie");
+        } else {
+            self.request.header.set("Cookie", value);
+        }
+    }
+
+    /// Drops request body and related headers
+    pub (crate) fn clear_body(&mut self) {
+        self.body.clear();
+        self.request.header.remove("content-length");
+        self.request.header.remove("content-type");
+        self.request.header.remove("transfer-encoding");
+    }
+
+    /// Clears redirect chain before new request.
+    /// Cookie and Authorization headers restored if chain was
+    /// on another origin and request url is on the first origin
+    fn reset_redirects(&mut self) {
+        if self.cross_origin {
+            let restore = self.redirect_origin
+                .as_ref()
+                .map(|v| is_same_origin(v, &self.request.url))
+                .unwrap_or(false);
+            if restore {
+                self.restore_redirect_headers();
+            }
+        }
+
+        self.redirects.clear();
+        self.redirect_requests.clear();
+        self.redirect_origin = None;
+        self.redirect_headers = (None, None);
+        self.redirect_pending = false;
+        self.cross_origin = false;
+    }
+
+    /// Sets Cookie and Authorization headers removed on redirect to another origin
+    fn restore_redirect_headers(&mut self) {
+        let (cookie, authorization) = mem::take(&mut self.redirect_headers);
+        self.jar_cookie.clear();
+        match cookie {
+            Some(v) => self.request.header.set("Cookie", v),
+            None => self.request.header.remove("cookie"),
+        }
+        if let Some(v) = authorization {
+            self.request.header.set("Authorization", v);
+        }
+    }
+
     /// Prepares for HTTP redirect to given location
+    ///
+    /// - 303: method changes to GET, request body drops
+    /// - 301, 302: POST changes to GET, request body drops
+    /// - 307, 308: method and request body preserved
+    ///
+    /// Authorization and Cookie headers removed while location has
+    /// another origin than the first request in the chain,
+    /// and restored when chain returns to the first origin.
+    /// Authentication in `get()` is disabled on another origin.
+    /// Returns RedirectLoop if request with the same url, method,
+    /// and cookies was sent before in the redirect chain.
+    /// Next `send()` continues the chain, other requests start new one
     pub fn redirect(&mut self) -> Result<()> {
         self.skip_body()?;
 
@@ -240,19 +911,115 @@ impl HttpClient {
             return Err(HttpClientError::InvalidRedirectLocation)
         }
 
-        if ! self.transfer.is_closed() {
-            let limit = std::cmp::min(location.len(), 8); // 8 - enough for "https://"
-            if location[.. limit].find("://").is_some() {
-                self.transfer.close();
-            }
+        if self.redirects.is_empty() {
+            self.redirects.push(self.request.url.as_url().to_string());
+            self.redirect_origin = Some(self.request.url.clone());
+        }
+
+        let mut url = self.request.url.clone();
+        url.set(location)?;
+
+        let method = self.request.get_method();
+        let rewrite = match self.response.get_code() {
+            303 => method != "GET" && method != "HEAD",
+            301 | 302 => method == "POST",
+            _ => false,
+        };
+
+        let cross_origin = match &self.redirect_origin {
+            Some(origin) => ! is_same_origin(&url, origin),
+            None => false,
+        };
+
+        // redirect loops only if the same request was sent before.
+        // location could be visited again with new cookies, e.g. after login
+        let user_cookie = if cross_origin {
+            ""
+        } else if self.cross_origin {
+            self.redirect_headers.0.as_deref().unwrap_or("")
+        } else {
+            self.get_user_cookie().unwrap_or("")
+        };
+        let cookie = match &self.cookies {
+            Some(jar) => join_cookie(user_cookie, &jar.get_cookie_except(&url, user_cookie).unwrap_or_default()),
+            None => user_cookie.to_owned(),
+        };
+        self.redirect_requests.push((
+            self.request.url.as_url().to_string(),
+            method.to_owned(),
+            self.request.header.get("cookie").unwrap_or("").to_owned()));
+
+        let location = url.as_url().to_string();
+        let method = if rewrite { "GET" } else { method };
+        let is_loop = self.redirect_requests.iter().any(|v| {
+            v.0 == location && v.1 == method && v.2 == cookie
+        });
+        if is_loop {
+            return Err(HttpClientError::RedirectLoop)
         }
 
-        self.request.url.set(location)?;
+        if rewrite {
+            self.request.set_method("GET");
+            self.clear_body();
+        }
+
+        if cross_origin && ! self.cross_origin {
+            self.redirect_headers = (
+                self.get_user_cookie().map(str::to_owned),
+                self.request.header.get("authorization").map(str::to_owned));
+            self.request.header.remove("authorization");
+            self.request.header.remove("cookie");
+        } else if ! cross_origin && self.cross_origin {
+            self.restore_redirect_headers();
+        }
+        self.cross_origin = cross_origin;
+
+        self.request.url = url;
+        self.request.header.set("Host", self.request.url.as_address());
+        self.redirects.push(location);
+        self.redirect_pending = true;
 
         Ok(())
     }
 
-    /// Simple GET request with authentication and location forwarding
+    /// Returns delay before retry or None if request should not be repeated
+    fn get_retry_delay(&self, attempt: usize, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.retry.max_retries {
+            return None
+        }
+
+        let delay = self.retry.get_delay(attempt, retry_after)?;
+        match self.deadline {
+            Some(deadline) if Instant::now() + delay >= deadline => None,
+            _ => Some(delay),
+        }
+    }
+
+    /// Sends request with body and receives response headers
+    fn io_exchange(&mut self) -> Result<()> {
+        self.io_send()?;
+        if ! self.body.is_empty() {
+            self.content.write_all(&self.body)?;
+        }
+        self.receive()
+    }
+
+    /// Sends request and receives response headers.
+    /// Unlike `receive()` request repeats with body
+    /// if reused connection was closed by server before response
+    fn exchange(&mut self) -> Result<()> {
+        let result = self.io_exchange();
+
+        let failed = result.is_err() || self.response.get_code() == 0;
+        if failed && self.is_stale_connection() {
+            self.close();
+            return self.io_exchange()
+        }
+
+        result
+    }
+
+    /// Simple GET request with authentication, location forwarding, and retries
     ///
     /// Usage:
     ///
@@ -266,22 +1033,115 @@ impl HttpClient {
     /// client.read_to_string(&mut body).unwrap();
     /// ```
     pub fn get(&mut self) -> Result<()> {
+        self.deadline = if self.total_timeout.as_nanos() != 0 {
+            Some(Instant::now() + self.total_timeout)
+        } else {
+            None
+        };
+
+        let result = self.io_get().map_err(check_timeout);
+        self.redirect_pending = false;
+
+        // content reads with socket timeouts instead of the rest of deadline
+        if self.deadline.take().is_some() {
+            self.content.as_transfer_mut().set_timeout(self.timeout)?;
+        }
+
+        result
+    }
+
+    fn io_get(&mut self) -> Result<()> {
         let mut attempt_auth = 0;
+        let mut attempt_stale = 0;
+        let mut attempt_proxy_auth = 0;
         let mut attempt_redirect = 0;
+        let mut attempt_retry = 0;
+        let mut unauthorized = false;
+        let max_redirects = self.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS);
+
+        self.reset_redirects();
 
         loop {
-            http_auth(&mut self.request, &self.response);
-            self.send()?;
-            self.receive()?;
+            let authorized = if ! self.cross_origin {
+                match &self.credentials {
+                    Some((username, password)) => self.auth.set_credentials(username, password),
+                    None => self.auth.set_credentials(
+                        self.request.url.get_username(),
+                        self.request.url.get_password()),
+                }
+                http_auth(&mut self.auth, &mut self.request, &self.response, &self.body)?
+            } else {
+                false
+            };
+
+            // nothing to answer the challenge with, same request fails again
+            if unauthorized && ! authorized {
+                return Err(HttpClientError::RequestFailed(
+                    401, self.response.get_reason().to_owned()));
+            }
+            unauthorized = false;
+
+            let idempotent = is_idempotent(self.request.get_method());
+
+            if let Err(e) = self.exchange() {
+                let e = check_timeout(e);
+                let delay = if idempotent && is_retryable(&e) {
+                    self.get_retry_delay(attempt_retry, None)
+                } else {
+                    None
+                };
+
+                match delay {
+                    Some(delay) => {
+                        self.close();
+                        thread::sleep(delay);
+                        attempt_retry += 1;
+                        continue;
+                    }
+                    None => return Err(e),
+                }
+            }
 
             match self.response.get_code() {
-                200 | 204 => break,
+                200 | 204 | 206 => break,
+                502 ..= 504 if idempotent && attempt_retry < self.retry.max_retries => {
+                    let retry_after = self.response.header
+                        .get("retry-after")
+                        .and_then(parse_retry_after);
+                    self.skip_body()?;
+
+                    match self.get_retry_delay(attempt_retry, retry_after) {
+                        Some(delay) => {
+                            thread::sleep(delay);
+                            attempt_retry += 1;
+                        }
+                        None => return Err(HttpClientError::RequestFailed(
+                            self.response.get_code(), self.response.get_reason().to_owned())),
+                    }
+                }
+                401 if attempt_stale < 2 && is_stale(&self.response) => {
+                    // repeat with new nonce
+                    self.skip_body()?;
+                    attempt_stale += 1;
+                    unauthorized = true;
+                }
                 401 if attempt_auth < 2 => {
                     self.skip_body()?;
                     // TODO: check url prefix
                     attempt_auth += 1;
+                    unauthorized = true;
                 }
-                301 | 302 if attempt_redirect < 3 => {
+                407 if attempt_proxy_auth < 2 &&
+                    self.request.header.get("proxy-authorization").is_some() =>
+                {
+                    self.skip_body()?;
+                    attempt_proxy_auth += 1;
+                }
+                301 | 302 | 303 | 307 | 308 => {
+                    if attempt_redirect >= max_redirects {
+                        self.skip_body()?;
+                        return Err(HttpClientError::TooManyRedirects);
+                    }
                     self.redirect()?;
                     attempt_redirect += 1;
                     attempt_auth = 0;
@@ -296,28 +1156,193 @@ impl HttpClient {
 
         Ok(())
     }
+
+    /// Downloads response body into `dst` starting from the current position.
+    /// Returns position of the end of content.
+    ///
+    /// If position is not zero, requests rest of the content with
+    /// `Range: bytes=N-` header. If server responds with 200 instead of 206,
+    /// content writes from the start of `dst`.
+    /// On read error request repeats from the received position
+    /// with If-Range header if response has ETag or Last-Modified.
+    /// `dst` is not truncated, so file should be truncated to the returned length
+    ///
+    /// Usage:
+    ///
+    /// ```no_run
+    /// use std::{
+    ///     fs::OpenOptions,
+    ///     io::{
+    ///         Seek,
+    ///         SeekFrom,
+    ///     },
+    /// };
+    /// use http::HttpClient;
+    ///
+    /// let mut file = OpenOptions::new()
+    ///     .create(true)
+    ///     .write(true)
+    ///     .open("/tmp/record.ts")
+    ///     .unwrap();
+    /// file.seek(SeekFrom::End(0)).unwrap();
+    ///
+    /// let mut client = HttpClient::new("http://nvr.local/record.ts").unwrap();
+    /// let len = client.download(&mut file).unwrap();
+    /// file.set_len(len).unwrap();
+    /// ```
+    pub fn download<W: Write + Seek>(&mut self, dst: &mut W) -> Result<u64> {
+        // range applies to encoded content, decoder could not start in the middle
+        let accept_encoding = self.request.header.get("accept-encoding").map(str::to_owned);
+        self.request.header.set("Accept-Encoding", "identity");
+
+        let result = self.io_download(dst);
+
+        self.request.header.remove("range");
+        self.request.header.remove("if-range");
+        match accept_encoding {
+            Some(v) => self.request.header.set("Accept-Encoding", v),
+            None => self.request.header.remove("accept-encoding"),
+        }
+
+        result
+    }
+
+    fn io_download<W: Write + Seek>(&mut self, dst: &mut W) -> Result<u64> {
+        let mut position = dst.stream_position()?;
+        let mut validator: Option<String> = None;
+        let mut attempt = 0;
+
+        loop {
+            if position != 0 {
+                self.request.header.set("Range", format!("bytes={}-", position));
+                match &validator {
+                    Some(v) => self.request.header.set("If-Range", v.as_str()),
+                    None => self.request.header.remove("if-range"),
+                }
+            } else {
+                self.request.header.remove("range");
+                self.request.header.remove("if-range");
+            }
+
+            let mut length = None;
+
+            match self.get() {
+                Ok(()) if self.response.get_code() == 206 => {
+                    let range = self.response.header.get("content-range").and_then(ContentRange::parse);
+                    match range {
+                        Some(ContentRange { range: Some((first, _)), length: v }) if first == position => {
+                            length = v;
+                        }
+                        _ => {
+                            self.close();
+                            return Err(HttpClientError::InvalidContentRange)
+                        }
+                    }
+                }
+                Ok(()) => {
+                    // complete content instead of range
+                    if position != 0 {
+                        position = dst.seek(SeekFrom::Start(0))?;
+                    }
+                    if let Some(v) = self.response.header.get("content-length") {
+                        length = v.parse().ok();
+                    }
+                }
+                Err(HttpClientError::RequestFailed(416, reason)) => {
+                    // requested position is the end of content
+                    let range = self.response.header.get("content-range").and_then(ContentRange::parse);
+                    match range {
+                        Some(ContentRange { range: None, length: Some(v) }) if v == position => {
+                            return Ok(position)
+                        }
+                        _ => return Err(HttpClientError::RequestFailed(416, reason)),
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+
+            if validator.is_none() {
+                validator = get_validator(&self.response);
+            }
+
+            let begin = position;
+            let result = loop {
+                let buf = match self.fill_buf() {
+                    Ok(v) => v,
+                    Err(e) => break Err(e),
+                };
+
+                if buf.is_empty() {
+                    break Ok(())
+                }
+
+                dst.write_all(buf)?;
+                let len = buf.len();
+                self.consume(len);
+                position += len as u64;
+            };
+
+            let complete = length.map(|v| position >= v).unwrap_or(true);
+            let error = match result {
+                Ok(()) if complete => return Ok(position),
+                Ok(()) => HttpClientError::Io(io::ErrorKind::UnexpectedEof.into()),
+                Err(e) => HttpClientError::Io(e),
+            };
+
+            self.close();
+
+            if position == begin {
+                attempt += 1;
+            } else {
+                attempt = 0;
+            }
+
+            // resume is not safe without validator
+            if validator.is_none() || attempt >= MAX_RESUME_ATTEMPTS {
+                return Err(check_timeout(error))
+            }
+        }
+    }
 }
 
 
 impl Read for HttpClient {
     #[inline]
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.transfer.read(buf) }
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.content.read(buf) }
 }
 
 
 impl BufRead for HttpClient {
     #[inline]
-    fn fill_buf(&mut self) -> io::Result<&[u8]> { self.transfer.fill_buf() }
+    fn fill_buf(&mut self) -> io::Result<&[u8]> { self.content.fill_buf() }
 
     #[inline]
-    fn consume(&mut self, amt: usize) { self.transfer.consume(amt) }
+    fn consume(&mut self, amt: usize) { self.content.consume(amt) }
 }
 
 
 impl Write for HttpClient {
     #[inline]
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.transfer.write(buf) }
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.content.write(buf) }
 
     #[inline]
-    fn flush(&mut self) -> io::Result<()> { self.transfer.flush() }
+    fn flush(&mut self) -> io::Result<()> { self.content.flush() }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::Url;
+    use super::is_same_origin;
+
+    #[test]
+    fn test_same_origin() {
+        let check = |a: &str, b: &str| is_same_origin(&Url::new(a).unwrap(), &Url::new(b).unwrap());
+        assert!(check("http://example.com/", "http://example.com:80/login"));
+        assert!(check("https://EXAMPLE.com:443/", "https://example.com/"));
+        assert!(check("rtsp://example.com/", "rtsp://example.com:554/"));
+        assert!(! check("http://example.com/", "https://example.com/"));
+        assert!(! check("http://example.com/", "http://example.com:8080/"));
+        assert!(! check("http://example.com/", "http://www.example.com/"));
+    }
 }
@@ -0,0 +1,431 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    io,
+    collections::{
+        HashMap,
+        VecDeque,
+    },
+    sync::{
+        Arc,
+        Condvar,
+        Mutex,
+        MutexGuard,
+        Weak,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use crate::client::transfer::stream::{
+    HttpStream,
+    HttpProxy,
+    HttpTls,
+};
+
+
+const DEFAULT_MAX_IDLE_PER_HOST: usize = 8;
+const DEFAULT_MAX_IDLE: usize = 256;
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+
+/// Connection origin: scheme, host, port, proxy to tunnel connection,
+/// and TLS context
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct HttpPoolKey {
+    scheme: String,
+    host: String,
+    port: u16,
+    proxy: Option<HttpProxy>,
+    tls: Option<HttpTls>,
+}
+
+
+impl HttpPoolKey {
+    pub fn new(scheme: &str, host: &str, port: u16, proxy: Option<HttpProxy>) -> Self {
+        HttpPoolKey {
+            scheme: scheme.to_ascii_lowercase(),
+            host: host.to_ascii_lowercase(),
+            port,
+            proxy,
+            tls: None,
+        }
+    }
+
+    /// Sets TLS context. By default used context with system defaults
+    #[inline]
+    pub fn set_tls(&mut self, tls: Option<HttpTls>) { self.tls = tls }
+
+    #[inline]
+    pub fn get_host(&self) -> &str { &self.host }
+
+    #[inline]
+    pub fn get_port(&self) -> u16 { self.port }
+
+    #[inline]
+    pub fn get_proxy(&self) -> Option<&HttpProxy> { self.proxy.as_ref() }
+
+    #[inline]
+    pub fn get_tls(&self) -> Option<&HttpTls> { self.tls.as_ref() }
+
+    /// Returns true if origin requires TLS
+    #[inline]
+    pub fn is_tls(&self) -> bool { self.scheme == "https" }
+}
+
+
+/// Open connection counted in the pool limits.
+/// Connection slot released on drop
+#[derive(Debug)]
+pub (crate) struct HttpPoolPermit {
+    pool: Weak<HttpPoolShared>,
+    key: HttpPoolKey,
+}
+
+
+impl Drop for HttpPoolPermit {
+    fn drop(&mut self) {
+        let shared = match self.pool.upgrade() {
+            Some(v) => v,
+            None => return,
+        };
+
+        let mut connections = shared.lock_connections();
+        connections.total -= 1;
+        if let Some(count) = connections.map.get_mut(&self.key) {
+            *count -= 1;
+            if *count == 0 {
+                connections.map.remove(&self.key);
+            }
+        }
+        drop(connections);
+
+        shared.released.notify_all();
+    }
+}
+
+
+#[derive(Debug)]
+struct HttpPoolEntry {
+    stream: HttpStream,
+    permit: HttpPoolPermit,
+    since: Instant,
+}
+
+
+#[derive(Debug)]
+struct HttpPoolInner {
+    max_idle_per_host: usize,
+    max_idle: usize,
+    idle_timeout: Duration,
+
+    idle: usize,
+    map: HashMap<HttpPoolKey, VecDeque<HttpPoolEntry>>,
+}
+
+
+impl Default for HttpPoolInner {
+    fn default() -> Self {
+        HttpPoolInner {
+            max_idle_per_host: DEFAULT_MAX_IDLE_PER_HOST,
+            max_idle: DEFAULT_MAX_IDLE,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+
+            idle: 0,
+            map: HashMap::new(),
+        }
+    }
+}
+
+
+impl HttpPoolInner {
+    /// Drops connections idle longer than timeout
+    fn evict_expired(&mut self) {
+        let now = Instant::now();
+        let timeout = self.idle_timeout;
+        let mut idle = 0;
+
+        self.map.retain(|_, list| {
+            list.retain(|entry| now.duration_since(entry.since) < timeout);
+            idle += list.len();
+            ! list.is_empty()
+        });
+
+        self.idle = idle;
+    }
+
+    /// Removes the oldest idle connection.
+    /// If `origin` defined connection selected only for this origin
+    fn remove_oldest(&mut self, origin: Option<&HttpPoolKey>) -> Option<HttpPoolEntry> {
+        let oldest = self.map.iter()
+            .filter(|(key, _)| origin.map(|v| v == *key).unwrap_or(true))
+            .filter_map(|(key, list)| list.front().map(|entry| (key, entry.since)))
+            .min_by_key(|(_, since)| *since)
+            .map(|(key, _)| key.clone())?;
+
+        let list = self.map.get_mut(&oldest).unwrap();
+        let entry = list.pop_front();
+        if list.is_empty() {
+            self.map.remove(&oldest);
+        }
+        self.idle -= 1;
+
+        entry
+    }
+
+    /// Drops the oldest idle connection
+    #[inline]
+    fn evict_oldest(&mut self) { self.remove_oldest(None); }
+}
+
+
+/// Number of the open connections: active and idle
+#[derive(Debug, Default)]
+struct HttpPoolConnections {
+    max_connections_per_host: usize,
+    max_connections: usize,
+
+    total: usize,
+    map: HashMap<HttpPoolKey, usize>,
+}
+
+
+impl HttpPoolConnections {
+    /// Checks that limit of connections to the origin reached
+    fn is_host_limit(&self, key: &HttpPoolKey) -> bool {
+        self.max_connections_per_host != 0 &&
+            self.map.get(key).copied().unwrap_or(0) >= self.max_connections_per_host
+    }
+
+    /// Checks that new connection to the origin could be opened
+    fn is_available(&self, key: &HttpPoolKey) -> bool {
+        (self.max_connections == 0 || self.total < self.max_connections) &&
+            ! self.is_host_limit(key)
+    }
+}
+
+
+#[derive(Debug, Default)]
+struct HttpPoolShared {
+    idle: Mutex<HttpPoolInner>,
+    connections: Mutex<HttpPoolConnections>,
+    /// notifies when connection closed
+    released: Condvar,
+}
+
+
+impl HttpPoolShared {
+    #[inline]
+    fn lock_connections(&self) -> MutexGuard<'_, HttpPoolConnections> {
+        self.connections.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+
+/// Shared pool of idle keep-alive connections
+///
+/// Connections are grouped by origin - scheme, host, and port.
+/// Pool could be shared between any number of `HttpClient`,
+/// each client takes idle connection to the same origin
+/// instead of opening new one.
+///
+/// Limits applied to the idle connections:
+///
+/// - `max_idle_per_host` - idle connections to the one origin. Default: 8
+/// - `max_idle` - idle connections in total. Default: 256
+/// - `idle_timeout` - connection dropped if not used during this time. Default: 60s
+///
+/// Limits applied to the open connections, active and idle:
+///
+/// - `max_connections_per_host` - connections to the one origin. Default: unlimited
+/// - `max_connections` - connections in total. Default: unlimited
+///
+/// If limit reached the oldest idle connection closes.
+/// Without idle connections client waits until other connection closed
+/// but not longer than connect timeout
+///
+/// Usage:
+///
+/// ```no_run
+/// use std::io::Read;
+/// use http::{
+///     HttpClient,
+///     HttpPool,
+/// };
+///
+/// let pool = HttpPool::default();
+///
+/// for _ in 0 .. 2 {
+///     let mut client = HttpClient::new("http://example.com").unwrap();
+///     client.set_pool(&pool);
+///     client.get().unwrap();
+///     let mut body = String::new();
+///     client.read_to_string(&mut body).unwrap();
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct HttpPool {
+    inner: Arc<HttpPoolShared>,
+}
+
+
+impl HttpPool {
+    /// Allocates new pool with default limits
+    #[inline]
+    pub fn new() -> Self { HttpPool::default() }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, HttpPoolInner> {
+        self.inner.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sets limit of the idle connections to the one origin
+    pub fn set_max_idle_per_host(&self, limit: usize) {
+        let mut inner = self.lock();
+        inner.max_idle_per_host = limit;
+        for list in inner.map.values_mut() {
+            while list.len() > limit {
+                list.pop_front();
+            }
+        }
+        inner.map.retain(|_, list| ! list.is_empty());
+        inner.idle = inner.map.values().map(VecDeque::len).sum();
+    }
+
+    /// Sets limit of the idle connections in total
+    pub fn set_max_idle(&self, limit: usize) {
+        let mut inner = self.lock();
+        inner.max_idle = limit;
+        while inner.idle > limit {
+            inner.evict_oldest();
+        }
+    }
+
+    /// Sets time to keep unused connection
+    pub fn set_idle_timeout(&self, timeout: Duration) {
+        let mut inner = self.lock();
+        inner.idle_timeout = timeout;
+        inner.evict_expired();
+    }
+
+    /// Sets limit of the open connections to the one origin.
+    /// Zero value disables limit
+    pub fn set_max_connections_per_host(&self, limit: usize) {
+        self.inner.lock_connections().max_connections_per_host = limit;
+        self.inner.released.notify_all();
+    }
+
+    /// Sets limit of the open connections in total.
+    /// Zero value disables limit
+    pub fn set_max_connections(&self, limit: usize) {
+        self.inner.lock_connections().max_connections = limit;
+        self.inner.released.notify_all();
+    }
+
+    /// Returns number of the idle connections
+    pub fn get_idle(&self) -> usize {
+        let mut inner = self.lock();
+        inner.evict_expired();
+        inner.idle
+    }
+
+    /// Returns number of the open connections: active and idle
+    pub fn get_connections(&self) -> usize {
+        self.lock().evict_expired();
+        self.inner.lock_connections().total
+    }
+
+    /// Reserves slot for the new connection to the origin.
+    /// If limits reached closes the oldest idle connection
+    /// or waits until other connection closed.
+    /// Zero `timeout` waits without limit
+    pub (crate) fn acquire(&self, key: &HttpPoolKey, timeout: Duration) -> io::Result<HttpPoolPermit> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let mut connections = self.inner.lock_connections();
+            if connections.is_available(key) {
+                connections.total += 1;
+                *connections.map.entry(key.clone()).or_insert(0) += 1;
+                return Ok(HttpPoolPermit {
+                    pool: Arc::downgrade(&self.inner),
+                    key: key.clone(),
+                })
+            }
+            let origin = if connections.is_host_limit(key) { Some(key) } else { None };
+            drop(connections);
+
+            // idle connection closes out of the lock
+            let entry = self.lock().remove_oldest(origin);
+            if entry.is_some() {
+                drop(entry);
+                continue
+            }
+
+            let connections = self.inner.lock_connections();
+            if connections.is_available(key) {
+                continue
+            }
+
+            if timeout.as_nanos() == 0 {
+                drop(self.inner.released.wait(connections).unwrap_or_else(|e| e.into_inner()));
+            } else {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "connection limit reached"))
+                }
+                drop(self.inner.released.wait_timeout(connections, deadline - now).unwrap_or_else(|e| e.into_inner()));
+            }
+        }
+    }
+
+    /// Takes most recently used idle connection to the origin
+    pub (crate) fn take(&self, key: &HttpPoolKey) -> Option<(HttpStream, HttpPoolPermit)> {
+        let mut inner = self.lock();
+        inner.evict_expired();
+
+        let list = inner.map.get_mut(key)?;
+        let entry = list.pop_back()?;
+        if list.is_empty() {
+            inner.map.remove(key);
+        }
+        inner.idle -= 1;
+
+        Some((entry.stream, entry.permit))
+    }
+
+    /// Returns idle connection into the pool.
+    /// Connection dropped if limits reached
+    pub (crate) fn put(&self, key: &HttpPoolKey, stream: HttpStream, permit: HttpPoolPermit) {
+        let mut inner = self.lock();
+        inner.evict_expired();
+
+        if inner.max_idle_per_host == 0 || inner.max_idle == 0 {
+            return;
+        }
+
+        let max_idle_per_host = inner.max_idle_per_host;
+        let list = inner.map.entry(key.clone()).or_default();
+        if list.len() >= max_idle_per_host {
+            list.pop_front();
+        } else {
+            inner.idle += 1;
+            if inner.idle > inner.max_idle {
+                inner.evict_oldest();
+            }
+        }
+
+        inner.map.entry(key.clone()).or_default().push_back(HttpPoolEntry {
+            stream,
+            permit,
+            since: Instant::now(),
+        });
+    }
+}
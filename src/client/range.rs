@@ -0,0 +1,99 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use crate::Response;
+
+
+/// Byte range of the partial response (RFC 7233 4.2)
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ContentRange {
+    /// first byte position. None for unsatisfied range `bytes */length`
+    pub range: Option<(u64, u64)>,
+    /// complete length. None if unknown
+    pub length: Option<u64>,
+}
+
+
+impl ContentRange {
+    /// Parses Content-Range header value
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.get(.. 6) {
+            Some(v) if v.eq_ignore_ascii_case("bytes ") => {}
+            _ => return None,
+        }
+
+        let mut i = value[6 ..].splitn(2, '/');
+        let range = i.next()?.trim();
+        let length = i.next()?.trim();
+
+        let length = match length {
+            "*" => None,
+            v => Some(v.parse().ok()?),
+        };
+
+        let range = match range {
+            "*" => None,
+            v => {
+                let mut i = v.splitn(2, '-');
+                let first: u64 = i.next()?.parse().ok()?;
+                let last: u64 = i.next()?.parse().ok()?;
+                if last < first || length.map(|v| last >= v).unwrap_or(false) {
+                    return None
+                }
+                Some((first, last))
+            }
+        };
+
+        if range.is_none() && length.is_none() {
+            return None
+        }
+
+        Some(ContentRange { range, length })
+    }
+}
+
+
+/// Returns strong validator for If-Range header:
+/// ETag if defined and not weak, otherwise Last-Modified
+pub fn get_validator(response: &Response) -> Option<String> {
+    if let Some(etag) = response.header.get("etag") {
+        if ! etag.starts_with("W/") {
+            return Some(etag.to_owned())
+        }
+    }
+
+    response.header.get("last-modified").map(str::to_owned)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_range() {
+        assert_eq!(ContentRange::parse("bytes 10-25/26"), Some(ContentRange {
+            range: Some((10, 25)),
+            length: Some(26),
+        }));
+        assert_eq!(ContentRange::parse("bytes 0-99/*"), Some(ContentRange {
+            range: Some((0, 99)),
+            length: None,
+        }));
+        assert_eq!(ContentRange::parse("bytes */1000"), Some(ContentRange {
+            range: None,
+            length: Some(1000),
+        }));
+        assert_eq!(ContentRange::parse("bytes 10-26/26"), None);
+        assert_eq!(ContentRange::parse("bytes 20-10/26"), None);
+        assert_eq!(ContentRange::parse("bytes */*"), None);
+        assert_eq!(ContentRange::parse("items 0-1/2"), None);
+        assert_eq!(ContentRange::parse("bytesé 0-1/2"), None);
+        assert_eq!(ContentRange::parse("байты 0-1/2"), None);
+    }
+}
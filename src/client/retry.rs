@@ -0,0 +1,159 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    io,
+    time::Duration,
+};
+
+use openssl::rand::rand_bytes;
+
+use crate::{
+    RequestError,
+    ResponseError,
+};
+
+use super::{
+    HttpClientError,
+    cookie,
+    transfer::{
+        HttpTransferError,
+        stream::HttpStreamError,
+    },
+};
+
+
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(200);
+const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+
+/// Retry policy for `HttpClient::get()`
+#[derive(Debug, Clone, Copy)]
+pub struct HttpRetry {
+    /// maximum number of retries. Zero disables retries
+    pub max_retries: usize,
+    /// delay before first retry
+    pub delay: Duration,
+    /// maximum delay between retries
+    pub max_delay: Duration,
+}
+
+
+impl Default for HttpRetry {
+    fn default() -> Self {
+        HttpRetry {
+            max_retries: 0,
+            delay: DEFAULT_RETRY_DELAY,
+            max_delay: DEFAULT_MAX_RETRY_DELAY,
+        }
+    }
+}
+
+
+impl HttpRetry {
+    /// Returns delay before retry: exponential backoff with jitter.
+    /// Delay from the Retry-After header is used if it is longer.
+    /// Returns None if Retry-After is longer than maximum delay
+    pub fn get_delay(&self, attempt: usize, retry_after: Option<Duration>) -> Option<Duration> {
+        let backoff = self.delay
+            .checked_mul(1 << attempt.min(16))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        // equal jitter: half of the delay is random
+        let mut buf = [0; 4];
+        rand_bytes(&mut buf).unwrap();
+        let jitter = f64::from(u32::from_ne_bytes(buf)) / f64::from(u32::MAX);
+        let backoff = backoff / 2 + (backoff / 2).mul_f64(jitter);
+
+        match retry_after {
+            Some(v) if v > self.max_delay => None,
+            Some(v) => Some(v.max(backoff)),
+            None => Some(backoff),
+        }
+    }
+}
+
+
+/// Checks that request method is idempotent (RFC 7231 4.2.2)
+pub fn is_idempotent(method: &str) -> bool {
+    matches!(method, "GET" | "HEAD" | "OPTIONS" | "TRACE" | "PUT" | "DELETE")
+}
+
+
+/// Checks that request could be repeated after error:
+/// failed connection, connection reset, or timeout
+pub fn is_retryable(e: &HttpClientError) -> bool {
+    let inner = match e {
+        HttpClientError::Timeout => return true,
+        HttpClientError::Io(e) => e,
+        HttpClientError::Request(RequestError::Io(e)) => e,
+        HttpClientError::Response(ResponseError::Io(e)) => e,
+        HttpClientError::HttpTransfer(HttpTransferError::Io(e)) => e,
+        HttpClientError::HttpTransfer(HttpTransferError::HttpStream(HttpStreamError::Io(e))) => e,
+        _ => return false,
+    };
+
+    matches!(inner.kind(),
+        io::ErrorKind::ConnectionRefused |
+        io::ErrorKind::ConnectionReset |
+        io::ErrorKind::ConnectionAborted |
+        io::ErrorKind::NotConnected |
+        io::ErrorKind::BrokenPipe |
+        io::ErrorKind::UnexpectedEof |
+        io::ErrorKind::TimedOut |
+        io::ErrorKind::WouldBlock)
+}
+
+
+/// Parses Retry-After header: delay in seconds or HTTP-date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(v) = value.parse::<u64>() {
+        return Some(Duration::from_secs(v))
+    }
+
+    let date = cookie::parse_date(value)?;
+    Some(Duration::from_secs(date.saturating_sub(cookie::now())))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay() {
+        let retry = HttpRetry {
+            max_retries: 5,
+            delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+
+        let v = retry.get_delay(0, None).unwrap();
+        assert!(v >= Duration::from_millis(50) && v <= Duration::from_millis(100));
+
+        let v = retry.get_delay(3, None).unwrap();
+        assert!(v >= Duration::from_millis(400) && v <= Duration::from_millis(800));
+
+        let v = retry.get_delay(30, None).unwrap();
+        assert!(v >= Duration::from_millis(500) && v <= Duration::from_secs(1));
+
+        assert_eq!(retry.get_delay(0, Some(Duration::from_secs(1))), Some(Duration::from_secs(1)));
+        assert_eq!(retry.get_delay(0, Some(Duration::from_secs(2))), None);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::from_secs(0)));
+        assert_eq!(parse_retry_after("soon"), None);
+        assert_eq!(parse_retry_after("Wed, 21 Окт 2015 07:28:00 GMT"), None);
+        assert_eq!(parse_retry_after("Wed, 21 Oé 2015 07:28:00 GMT"), None);
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    fmt,
+    io::{
+        self,
+        Read,
+        Write,
+    },
+};
+
+use crate::{
+    Request,
+    Response,
+    ResponseError,
+};
+
+use super::{
+    Result,
+    transfer::HttpTransfer,
+};
+
+
+type RtspRequestHandler = Box<dyn FnMut(&Request, &[u8], &mut dyn Write) -> std::result::Result<(), ResponseError> + Send>;
+
+
+/// Handler for the requests from RTSP server (RFC 2326 10, RFC 7826 13)
+#[derive(Default)]
+pub struct RtspHandler {
+    handler: Option<RtspRequestHandler>,
+}
+
+
+impl fmt::Debug for RtspHandler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RtspHandler")
+            .field("handler", &self.handler.is_some())
+            .finish()
+    }
+}
+
+
+impl RtspHandler {
+    #[inline]
+    pub fn set_handler(&mut self, handler: RtspRequestHandler) { self.handler = Some(handler) }
+
+    /// Reads request and body from the stream, sends response.
+    /// Without handler OPTIONS is accepted, other methods are not implemented
+    pub fn process(&mut self, transfer: &mut HttpTransfer) -> Result<()> {
+        let mut request = Request::default();
+        request.parse(transfer)?;
+
+        let len = request.header.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let mut body = Vec::new();
+        let n = io::copy(&mut Read::by_ref(transfer).take(len), &mut body)?;
+        if n != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())
+        }
+
+        match &mut self.handler {
+            Some(handler) => handler(&request, &body, transfer)?,
+            None => reply_default(&request, transfer)?,
+        }
+
+        transfer.flush()?;
+        Ok(())
+    }
+}
+
+
+fn reply_default(request: &Request, dst: &mut dyn Write) -> std::result::Result<(), ResponseError> {
+    let mut response = Response::default();
+    response.set_version(request.get_version());
+
+    if request.get_method() == "OPTIONS" {
+        response.set_code(200);
+        response.set_reason("OK");
+    } else {
+        response.set_code(501);
+        response.set_reason("Not Implemented");
+    }
+
+    if let Some(cseq) = request.header.get("cseq") {
+        response.header.set("CSeq", cseq);
+    }
+
+    response.send(dst)
+}
@@ -10,6 +10,7 @@ use std::{
     io::{
         self,
         Read,
+        Write,
     },
 };
 
@@ -18,6 +19,35 @@ use super::{
     HttpTransferExt,
 };
 
+use crate::Header;
+
+
+/// Writes data as a single chunk: chunk-size, data, and CRLF.
+/// Chunk prepared in one buffer to send it with one write operation
+pub fn write_chunk(dst: &mut dyn Write, data: &[u8]) -> io::Result<()> {
+    if data.is_empty() {
+        return Ok(())
+    }
+
+    let mut chunk = Vec::with_capacity(data.len() + 12);
+    write!(chunk, "{:x}\r\n", data.len())?;
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(b"\r\n");
+    dst.write_all(&chunk)
+}
+
+
+/// Writes last chunk with optional trailer
+pub fn write_last_chunk(dst: &mut dyn Write, trailer: Option<&Header>) -> io::Result<()> {
+    let mut chunk = Vec::with_capacity(256);
+    chunk.extend_from_slice(b"0\r\n");
+    if let Some(trailer) = trailer {
+        trailer.send(&mut chunk)?;
+    }
+    chunk.extend_from_slice(b"\r\n");
+    dst.write_all(&chunk)
+}
+
 
 #[derive(Debug)]
 pub struct HttpChunked {
@@ -0,0 +1,55 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+
+/// Handler for the data of the interleaved channel
+pub type InterleavedHandler = Box<dyn FnMut(&[u8]) + Send>;
+
+
+/// Demultiplexer for the RTSP interleaved binary data (RFC 2326 10.12).
+/// Frames for channels without handler are dropped
+#[derive(Default)]
+pub struct HttpInterleaved {
+    channels: Vec<(u8, InterleavedHandler)>,
+    /// payload of the last frame
+    pub buf: Vec<u8>,
+}
+
+
+impl fmt::Debug for HttpInterleaved {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.channels.iter().map(|(channel, _)| channel))
+            .finish()
+    }
+}
+
+
+impl HttpInterleaved {
+    /// Sets handler for the channel. Replaces previous handler
+    pub fn set_handler(&mut self, channel: u8, handler: InterleavedHandler) {
+        match self.channels.iter_mut().find(|(v, _)| *v == channel) {
+            Some(item) => item.1 = handler,
+            None => self.channels.push((channel, handler)),
+        }
+    }
+
+    /// Removes handler for the channel
+    pub fn remove_handler(&mut self, channel: u8) {
+        self.channels.retain(|(v, _)| *v != channel)
+    }
+
+    /// Passes payload from the buffer to the channel handler
+    pub fn dispatch(&mut self, channel: u8) {
+        let data = &self.buf;
+        if let Some((_, handler)) = self.channels.iter_mut().find(|(v, _)| *v == channel) {
+            handler(data)
+        }
+    }
+}
@@ -46,7 +46,9 @@ impl HttpTransferExt for HttpLength {
             buf.pos = 0;
         }
 
-        Ok(&buf.buf[buf.pos .. buf.cap])
+        // buffer could contain next message
+        let len = cmp::min(buf.cap - buf.pos, self.len);
+        Ok(&buf.buf[buf.pos .. buf.pos + len])
     }
 
     #[inline]
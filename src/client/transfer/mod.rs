@@ -8,6 +8,7 @@
 use std::{
     cmp,
     fmt,
+    mem,
     io::{
         self,
         BufRead,
@@ -20,13 +21,18 @@ pub (crate) mod stream;
 use self::stream::{
     HttpStream,
     HttpStreamError,
+    HttpTimeout,
 };
 
 pub (crate) mod buffer;
 use self::buffer::HttpBuffer;
 
 mod chunked;
-use self::chunked::HttpChunked;
+use self::chunked::{
+    HttpChunked,
+    write_chunk,
+    write_last_chunk,
+};
 
 mod length;
 use self::length::HttpLength;
@@ -34,6 +40,19 @@ use self::length::HttpLength;
 mod persist;
 use self::persist::HttpPersist;
 
+mod interleaved;
+pub use self::interleaved::InterleavedHandler;
+use self::interleaved::HttpInterleaved;
+
+use crate::{
+    Header,
+    client::pool::{
+        HttpPool,
+        HttpPoolKey,
+        HttpPoolPermit,
+    },
+};
+
 
 trait HttpTransferExt: fmt::Debug {
     fn fill_buf<'a>(&mut self, buf: &'a mut HttpBuffer, src: &mut dyn Read) -> io::Result<&'a [u8]>;
@@ -72,9 +91,11 @@ enum HttpConnection {
 /// Supports next features:
 ///
 /// - buffering reader and writer
-/// - chunked transfer-encoding
+/// - chunked transfer-encoding for reading and writing
 /// - returns EOF if content completely readed or connection closed
 /// - keep-alive
+/// - connection pool
+/// - RTSP interleaved frames
 #[derive(Debug)]
 pub struct HttpTransfer {
     stream: HttpStream,
@@ -83,6 +104,18 @@ pub struct HttpTransfer {
 
     transfer: Box<dyn HttpTransferExt>,
     connection: HttpConnection,
+    write_chunked: bool,
+    /// connection opened before current request
+    reused: bool,
+    /// response data received on current request
+    received: bool,
+    /// handlers for RTSP interleaved frames
+    interleaved: HttpInterleaved,
+
+    origin: HttpPoolKey,
+    pool: Option<HttpPool>,
+    /// slot of the current connection in the pool limits
+    permit: Option<HttpPoolPermit>,
 }
 
 
@@ -95,6 +128,14 @@ impl Default for HttpTransfer {
 
             transfer: Box::new(HttpPersist),
             connection: HttpConnection::None,
+            write_chunked: false,
+            reused: false,
+            received: false,
+            interleaved: HttpInterleaved::default(),
+
+            origin: HttpPoolKey::default(),
+            pool: None,
+            permit: None,
         }
     }
 }
@@ -106,26 +147,106 @@ impl HttpTransfer {
     pub fn close(&mut self) {
         self.connection = HttpConnection::None;
         self.stream.close();
+        self.permit = None;
+    }
+
+    /// Sets socket timeouts for the current connection
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: HttpTimeout) -> io::Result<()> {
+        self.stream.set_timeout(timeout)
+    }
+
+    /// Sets shared pool for idle connections
+    #[inline]
+    pub fn set_pool(&mut self, pool: HttpPool) { self.pool = Some(pool) }
+
+    /// Returns true if TLS session of the current connection was resumed
+    #[inline]
+    pub fn is_session_reused(&self) -> bool { self.stream.is_session_reused() }
+
+    /// Returns true if connection was opened before current request:
+    /// kept alive or taken from the pool
+    #[inline]
+    pub fn is_reused(&self) -> bool { self.reused }
+
+    /// Returns true if any response data received since `connect()`
+    #[inline]
+    pub fn is_received(&self) -> bool { self.received }
+
+    /// Returns idle connection into the pool if defined
+    fn release(&mut self) {
+        if let (Some(pool), Some(permit)) = (&self.pool, self.permit.take()) {
+            let stream = mem::take(&mut self.stream);
+            pool.put(&self.origin, stream, permit);
+            self.connection = HttpConnection::None;
+        }
+    }
+
+    /// Completes response on the end of content.
+    /// Connection closes or returns into the pool
+    fn complete(&mut self) {
+        match self.connection {
+            HttpConnection::Close => self.close(),
+            HttpConnection::KeepAlive => {
+                self.connection = HttpConnection::Ready;
+                self.release();
+            }
+            _ => {}
+        }
     }
 
     /// Opens a TCP connection to a remote host
-    /// If connection already opened just clears read/write buffers
-    pub fn connect(&mut self, tls: bool, host: &str, port: u16) -> Result<()> {
-        self.rbuf.clear();
+    /// If connection to the same origin already opened just clears read/write buffers.
+    /// Connection to another origin returns into the pool or closes
+    pub fn connect(&mut self, origin: &HttpPoolKey, timeout: HttpTimeout) -> Result<()> {
+        // data after complete response is kept for the next response,
+        // e.g. RTSP interleaved frames
+        if self.connection != HttpConnection::Ready {
+            self.rbuf.clear();
+        }
         self.wbuf.clear();
         self.transfer = Box::new(HttpPersist);
+        self.write_chunked = false;
+        self.reused = true;
+        self.received = false;
+
+        if self.connection != HttpConnection::None && &self.origin != origin {
+            if self.connection == HttpConnection::Ready && self.pool.is_some() {
+                self.release();
+            } else {
+                self.close();
+            }
+        }
 
         if self.connection == HttpConnection::None {
-            self.stream.connect(tls, host, port)?;
+            self.origin.clone_from(origin);
+            self.rbuf.clear();
+
+            let stream = self.pool.as_ref().and_then(|pool| pool.take(origin));
+            match stream {
+                Some((stream, permit)) => {
+                    self.stream = stream;
+                    self.permit = Some(permit);
+                }
+                None => {
+                    self.reused = false;
+                    self.permit = match &self.pool {
+                        Some(pool) => Some(pool.acquire(origin, timeout.connect)?),
+                        None => None,
+                    };
+                    self.stream.set_timeout(timeout)?;
+                    self.stream.connect(origin)?;
+                }
+            }
+
             self.connection = HttpConnection::Ready;
         }
 
+        self.stream.set_timeout(timeout)?;
+
         Ok(())
     }
 
-    #[inline]
-    pub fn is_closed(&self) -> bool { self.connection == HttpConnection::None }
-
     /// Close connection after end of response
     #[inline]
     pub fn set_connection_close(&mut self) { self.connection = HttpConnection::Close }
@@ -137,9 +258,6 @@ impl HttpTransfer {
     /// Content-Length defined in the headers or response without content
     #[inline]
     pub fn set_content_length(&mut self, len: usize) {
-        if self.rbuf.cap - self.rbuf.pos > len {
-            self.rbuf.cap = self.rbuf.pos + len;
-        }
         self.transfer = Box::new(HttpLength::new(len))
     }
 
@@ -150,31 +268,148 @@ impl HttpTransfer {
     /// Receive content until connection closed
     #[inline]
     pub fn set_content_persist(&mut self) { self.transfer = Box::new(HttpPersist) }
+
+    /// Transfer-Encoding: chunked for writing.
+    /// Should be set after request headers flushed
+    #[inline]
+    pub fn set_write_chunked(&mut self) { self.write_chunked = true }
+
+    /// Completes chunked content with last chunk and optional trailer.
+    /// Do nothing if chunked writing not enabled
+    pub fn finish(&mut self, trailer: Option<&Header>) -> io::Result<()> {
+        if ! self.write_chunked {
+            return Ok(())
+        }
+
+        self.flush_buf()?;
+        self.write_chunked = false;
+        write_last_chunk(&mut self.stream, trailer)?;
+        self.stream.flush()
+    }
+
+    /// Sets handler for the RTSP interleaved channel
+    #[inline]
+    pub fn set_interleaved(&mut self, channel: u8, handler: InterleavedHandler) {
+        self.interleaved.set_handler(channel, handler)
+    }
+
+    /// Removes handler for the RTSP interleaved channel
+    #[inline]
+    pub fn remove_interleaved(&mut self, channel: u8) { self.interleaved.remove_handler(channel) }
+
+    /// Reads from the stream until buffer contains at least `len` bytes
+    fn fill_raw(&mut self, len: usize) -> io::Result<()> {
+        while self.rbuf.cap - self.rbuf.pos < len {
+            if self.rbuf.pos != 0 {
+                self.rbuf.buf.copy_within(self.rbuf.pos .. self.rbuf.cap, 0);
+                self.rbuf.cap -= self.rbuf.pos;
+                self.rbuf.pos = 0;
+            }
+
+            let n = self.stream.read(&mut self.rbuf.buf[self.rbuf.cap ..])?;
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into())
+            }
+            self.rbuf.cap += n;
+            self.received = true;
+        }
+
+        Ok(())
+    }
+
+    /// Reads RTSP interleaved frame: `$`, channel, 16-bit length, and payload.
+    /// Payload passes to the channel handler.
+    /// Returns false if next data in the stream is not a frame.
+    /// Should be called between messages only
+    pub fn read_frame(&mut self) -> io::Result<bool> {
+        self.fill_raw(1)?;
+        if self.rbuf.buf[self.rbuf.pos] != b'$' {
+            return Ok(false)
+        }
+
+        self.fill_raw(4)?;
+        let head = &self.rbuf.buf[self.rbuf.pos .. self.rbuf.pos + 4];
+        let channel = head[1];
+        let len = usize::from(u16::from_be_bytes([head[2], head[3]]));
+        self.rbuf.pos += 4;
+
+        let data = &mut self.interleaved.buf;
+        data.clear();
+
+        let n = cmp::min(len, self.rbuf.cap - self.rbuf.pos);
+        data.extend_from_slice(&self.rbuf.buf[self.rbuf.pos .. self.rbuf.pos + n]);
+        self.rbuf.pos += n;
+
+        if n < len {
+            data.resize(len, 0);
+            self.stream.read_exact(&mut data[n ..])?;
+        }
+
+        self.interleaved.dispatch(channel);
+        Ok(true)
+    }
+
+    /// Checks that next RTSP message in the stream is a request.
+    /// Response starts with protocol version.
+    /// Line breaks between messages are skipped
+    pub fn is_request(&mut self) -> io::Result<bool> {
+        loop {
+            self.fill_raw(1)?;
+            match self.rbuf.buf[self.rbuf.pos] {
+                b'\r' | b'\n' => self.rbuf.pos += 1,
+                _ => break,
+            }
+        }
+
+        self.fill_raw(5)?;
+        Ok(&self.rbuf.buf[self.rbuf.pos .. self.rbuf.pos + 5] != b"RTSP/")
+    }
+
+    /// Writes buffered data into the stream
+    fn flush_buf(&mut self) -> io::Result<()> {
+        if self.write_chunked {
+            write_chunk(&mut self.stream, &self.wbuf.buf[self.wbuf.pos .. self.wbuf.cap])?;
+            self.wbuf.clear();
+            return Ok(())
+        }
+
+        while self.wbuf.pos < self.wbuf.cap {
+            match self.stream.write(&self.wbuf.buf[self.wbuf.pos .. self.wbuf.cap]) {
+                Ok(0) => {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero,
+                        "failed to write the buffered data"));
+                },
+                Ok(n) => { self.wbuf.pos += n },
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => {
+                    return Err(e);
+                },
+            }
+        }
+        self.wbuf.clear();
+        Ok(())
+    }
 }
 
 
 impl Read for HttpTransfer {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut rem = self.fill_buf()?;
-        if ! rem.is_empty() {
-            let nread = rem.read(buf)?;
-            self.consume(nread);
-            Ok(nread)
-        } else {
-            if self.connection == HttpConnection::Close {
-                self.close();
-            } else {
-                self.connection = HttpConnection::Ready;
-            }
-            Ok(0)
-        }
+        let nread = rem.read(buf)?;
+        self.consume(nread);
+        Ok(nread)
     }
 }
 
 
 impl BufRead for HttpTransfer {
-    #[inline]
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.transfer.fill_buf(&mut self.rbuf, &mut self.stream)?.is_empty() {
+            self.complete();
+            return Ok(&[])
+        }
+
+        self.received = true;
         self.transfer.fill_buf(&mut self.rbuf, &mut self.stream)
     }
 
@@ -193,7 +428,12 @@ impl Write for HttpTransfer {
         }
 
         if buf.len() >= self.wbuf.buf.len() {
-            self.stream.write(buf)
+            if self.write_chunked {
+                write_chunk(&mut self.stream, buf)?;
+                Ok(buf.len())
+            } else {
+                self.stream.write(buf)
+            }
         } else {
             let r = (&mut self.wbuf.buf[self.wbuf.cap ..]).write(buf)?;
             self.wbuf.cap += r;
@@ -202,20 +442,7 @@ impl Write for HttpTransfer {
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        while self.wbuf.pos < self.wbuf.cap {
-            match self.stream.write(&self.wbuf.buf[self.wbuf.pos .. self.wbuf.cap]) {
-                Ok(0) => {
-                    return Err(io::Error::new(io::ErrorKind::WriteZero,
-                        "failed to write the buffered data"));
-                },
-                Ok(n) => { self.wbuf.pos += n },
-                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
-                Err(e) => {
-                    return Err(e);
-                },
-            }
-        }
-        self.wbuf.clear();
+        self.flush_buf()?;
         self.stream.flush()
     }
 }
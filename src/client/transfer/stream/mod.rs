@@ -20,17 +20,36 @@ use {
         time::Duration,
     },
 
-    openssl::ssl::{
-        SslMethod,
-        SslConnector,
-        SslStream,
-    },
+    openssl::ssl::SslStream,
 };
 
 
 mod null;
 pub (crate) use self::null::NullStream;
 
+mod proxy;
+pub (crate) use self::proxy::{
+    HttpProxy,
+    HttpProxyKind,
+};
+
+mod socks5;
+
+mod tls;
+pub use self::tls::{
+    HttpTls,
+    HttpTlsConfig,
+    HttpTlsVersion,
+};
+
+use crate::{
+    ResponseError,
+    client::{
+        auth::HttpAuthError,
+        pool::HttpPoolKey,
+    },
+};
+
 
 #[derive(Debug, Error)]
 pub enum HttpStreamError {
@@ -40,24 +59,106 @@ pub enum HttpStreamError {
     Ssl(openssl::error::ErrorStack),
     #[error_from("Handshake: {}", 0)]
     Handshake(openssl::ssl::HandshakeError<TcpStream>),
+    #[error_from("Proxy: {}", 0)]
+    ProxyResponse(ResponseError),
+    #[error_kind("Proxy: {} {}", 0, 1)]
+    ProxyFailed(usize, String),
+    #[error_from("Proxy: {}", 0)]
+    ProxyAuth(HttpAuthError),
+    #[error_kind("SOCKS5: {}", 0)]
+    Socks5(&'static str),
+    #[error_kind("TLS: public key pin mismatch for {}", 0)]
+    PinMismatch(String),
 }
 
 
 type Result<T> = std::result::Result<T, HttpStreamError>;
 
 
-trait Stream: Read + Write + fmt::Debug {}
+trait Stream: Read + Write + fmt::Debug + Send {
+    /// Returns reference to the TCP socket
+    fn as_tcp(&self) -> Option<&TcpStream>;
+
+    /// Returns true if TLS session was resumed
+    fn is_session_reused(&self) -> bool { false }
+
+    /// Sends TLS close notify.
+    /// Session of the connection closed without notify could not be resumed
+    fn shutdown(&mut self) {}
+}
 
 
-impl Stream for NullStream {}
-impl Stream for TcpStream {}
-impl Stream for SslStream<TcpStream> {}
+impl Stream for NullStream {
+    #[inline]
+    fn as_tcp(&self) -> Option<&TcpStream> { None }
+}
+
+
+impl Stream for TcpStream {
+    #[inline]
+    fn as_tcp(&self) -> Option<&TcpStream> { Some(self) }
+}
+
+
+impl Stream for SslStream<TcpStream> {
+    #[inline]
+    fn as_tcp(&self) -> Option<&TcpStream> { Some(self.get_ref()) }
+
+    #[inline]
+    fn is_session_reused(&self) -> bool { self.ssl().session_reused() }
+
+    #[inline]
+    fn shutdown(&mut self) { let _ = SslStream::shutdown(self); }
+}
+
+
+/// Socket timeouts. Zero value disables timeout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpTimeout {
+    /// Timeout for TCP connection
+    pub connect: Duration,
+    /// Timeout for each read operation
+    pub read: Duration,
+    /// Timeout for each write operation
+    pub write: Duration,
+}
+
+
+impl Default for HttpTimeout {
+    fn default() -> Self {
+        HttpTimeout {
+            connect: Duration::from_secs(3),
+            read: Duration::from_secs(3),
+            write: Duration::from_secs(3),
+        }
+    }
+}
+
+
+impl HttpTimeout {
+    /// Limits all timeouts with given value
+    pub fn limit(&self, limit: Duration) -> Self {
+        let f = |v: Duration| if v.as_nanos() == 0 || v > limit { limit } else { v };
+
+        HttpTimeout {
+            connect: f(self.connect),
+            read: f(self.read),
+            write: f(self.write),
+        }
+    }
+}
+
+
+#[inline]
+fn as_option(v: Duration) -> Option<Duration> {
+    if v.as_nanos() == 0 { None } else { Some(v) }
+}
 
 
 /// HTTP socket - abstraction over TcpStream or SslStream
 #[derive(Debug)]
 pub struct HttpStream {
-    timeout: Duration,
+    timeout: HttpTimeout,
     inner: Box<dyn Stream>,
 }
 
@@ -65,7 +166,7 @@ pub struct HttpStream {
 impl Default for HttpStream {
     fn default() -> Self {
         HttpStream {
-            timeout: Duration::from_secs(3),
+            timeout: HttpTimeout::default(),
             inner: Box::new(NullStream),
         }
     }
@@ -76,17 +177,40 @@ impl HttpStream {
     /// Close connection
     #[inline]
     pub fn close(&mut self) {
+        self.inner.shutdown();
         self.inner = Box::new(NullStream);
     }
 
+    /// Returns true if TLS session was resumed with abbreviated handshake
+    #[inline]
+    pub fn is_session_reused(&self) -> bool { self.inner.is_session_reused() }
+
+    /// Sets socket timeouts
+    /// Read and write timeouts applies to the opened connection immediately
+    pub fn set_timeout(&mut self, timeout: HttpTimeout) -> io::Result<()> {
+        self.timeout = timeout;
+
+        if let Some(v) = self.inner.as_tcp() {
+            v.set_read_timeout(as_option(self.timeout.read))?;
+            v.set_write_timeout(as_option(self.timeout.write))?;
+        }
+
+        Ok(())
+    }
+
     fn io_connect(&self, host: &str, port: u16) -> io::Result<TcpStream> {
         let mut last_err = None;
         let addrs = (host, port).to_socket_addrs()?;
         for addr in addrs {
-            match TcpStream::connect_timeout(&addr, self.timeout) {
+            let result = match as_option(self.timeout.connect) {
+                Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                None => TcpStream::connect(addr),
+            };
+
+            match result {
                 Ok(v) => {
-                    v.set_read_timeout(Some(self.timeout))?;
-                    v.set_write_timeout(Some(self.timeout))?;
+                    v.set_read_timeout(as_option(self.timeout.read))?;
+                    v.set_write_timeout(as_option(self.timeout.write))?;
 
                     return Ok(v)
                 },
@@ -98,16 +222,32 @@ impl HttpStream {
             io::Error::new(io::ErrorKind::InvalidInput, "address resolve failed")))
     }
 
-    /// Opens a TCP connection to a remote host
-    pub fn connect(&mut self, tls: bool, host: &str, port: u16) -> Result<()> {
-        let stream = self.io_connect(host, port)?;
+    /// Opens a TCP connection to a remote host directly or through the proxy
+    pub fn connect(&mut self, origin: &HttpPoolKey) -> Result<()> {
+        let tls = origin.is_tls();
+        let host = origin.get_host();
+        let port = origin.get_port();
+
+        let stream = match origin.get_proxy() {
+            None => self.io_connect(host, port)?,
+            Some(proxy) => match proxy.get_kind() {
+                HttpProxyKind::Http => proxy.connect(host, port, || {
+                    self.io_connect(proxy.get_host(), proxy.get_port())
+                })?,
+                HttpProxyKind::Socks5 => {
+                    let mut stream = self.io_connect(proxy.get_host(), proxy.get_port())?;
+                    socks5::connect(&mut stream, proxy.get_username(), proxy.get_password(), host, port)?;
+                    stream
+                }
+            },
+        };
 
         if tls {
-            let connector = SslConnector::builder(SslMethod::tls())?;
-            let mut ssl = connector.build().configure()?;
-            ssl.set_use_server_name_indication(true);
-            ssl.set_verify_hostname(true);
-            let stream = ssl.connect(host, stream)?;
+            let tls = match origin.get_tls() {
+                Some(v) => v.clone(),
+                None => HttpTls::global()?,
+            };
+            let stream = tls.connect(host, port, stream)?;
             self.inner = Box::new(stream);
         } else {
             self.inner = Box::new(stream);
@@ -118,6 +258,12 @@ impl HttpStream {
 }
 
 
+impl Drop for HttpStream {
+    #[inline]
+    fn drop(&mut self) { self.inner.shutdown() }
+}
+
+
 impl Read for HttpStream {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.inner.read(buf) }
@@ -141,7 +287,7 @@ mod tests {
         use std::io::{Read, Write};
 
         let mut socket = HttpStream::default();
-        socket.connect(true, "example.com", 443).unwrap();
+        socket.connect(&HttpPoolKey::new("https", "example.com", 443, None)).unwrap();
         socket.write_all(concat!("GET / HTTP/1.0\r\n",
             "Host: example.com\r\n",
             "User-Agent: libhttp\r\n",
@@ -0,0 +1,159 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    io::{
+        Read,
+        Write,
+    },
+    net::{
+        IpAddr,
+        TcpStream,
+    },
+};
+
+use super::{
+    HttpStreamError,
+    Result,
+};
+
+
+const VERSION: u8 = 0x05;
+
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_USERPASS: u8 = 0x02;
+const METHOD_NOT_ACCEPTABLE: u8 = 0xFF;
+
+const USERPASS_VERSION: u8 = 0x01;
+
+const CMD_CONNECT: u8 = 0x01;
+
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+
+fn reply_error(code: u8) -> &'static str {
+    match code {
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+
+/// Username/Password authentication (RFC 1929)
+fn auth(stream: &mut TcpStream, username: &str, password: &str) -> Result<()> {
+    let username = username.as_bytes();
+    let password = password.as_bytes();
+
+    if username.len() > 255 || password.len() > 255 {
+        return Err(HttpStreamError::Socks5("credentials too long"));
+    }
+
+    let mut buf = Vec::with_capacity(3 + username.len() + password.len());
+    buf.push(USERPASS_VERSION);
+    buf.push(username.len() as u8);
+    buf.extend_from_slice(username);
+    buf.push(password.len() as u8);
+    buf.extend_from_slice(password);
+    stream.write_all(&buf)?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply)?;
+    if reply[1] != 0x00 {
+        return Err(HttpStreamError::Socks5("authentication failed"));
+    }
+
+    Ok(())
+}
+
+
+/// Opens tunnel to the destination host through SOCKS5 proxy (RFC 1928).
+/// Domain name resolves on the proxy side.
+/// Empty `username` means that authentication is not required
+pub fn connect(stream: &mut TcpStream, username: &str, password: &str, host: &str, port: u16) -> Result<()> {
+    // greeting
+    if username.is_empty() {
+        stream.write_all(&[VERSION, 1, METHOD_NO_AUTH])?;
+    } else {
+        stream.write_all(&[VERSION, 2, METHOD_NO_AUTH, METHOD_USERPASS])?;
+    }
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply)?;
+    if reply[0] != VERSION {
+        return Err(HttpStreamError::Socks5("invalid version"));
+    }
+
+    match reply[1] {
+        METHOD_NO_AUTH => {}
+        METHOD_USERPASS if ! username.is_empty() => auth(stream, username, password)?,
+        METHOD_NOT_ACCEPTABLE => return Err(HttpStreamError::Socks5("authentication method not acceptable")),
+        _ => return Err(HttpStreamError::Socks5("invalid authentication method")),
+    }
+
+    // connect request
+    let mut buf = Vec::with_capacity(262);
+    buf.extend_from_slice(&[VERSION, CMD_CONNECT, 0x00]);
+
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(v)) => {
+            buf.push(ATYP_IPV4);
+            buf.extend_from_slice(&v.octets());
+        }
+        Ok(IpAddr::V6(v)) => {
+            buf.push(ATYP_IPV6);
+            buf.extend_from_slice(&v.octets());
+        }
+        Err(_) => {
+            if host.is_empty() || host.len() > 255 {
+                return Err(HttpStreamError::Socks5("invalid host name"));
+            }
+            buf.push(ATYP_DOMAIN);
+            buf.push(host.len() as u8);
+            buf.extend_from_slice(host.as_bytes());
+        }
+    }
+
+    buf.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&buf)?;
+
+    // reply: VER REP RSV ATYP BND.ADDR BND.PORT
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply)?;
+    if reply[0] != VERSION {
+        return Err(HttpStreamError::Socks5("invalid version"));
+    }
+    if reply[1] != 0x00 {
+        return Err(HttpStreamError::Socks5(reply_error(reply[1])));
+    }
+
+    let addr_len = match reply[3] {
+        ATYP_IPV4 => 4,
+        ATYP_IPV6 => 16,
+        ATYP_DOMAIN => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len)?;
+            usize::from(len[0])
+        }
+        _ => return Err(HttpStreamError::Socks5("invalid address type")),
+    };
+
+    // skip bound address and port
+    let mut bound = [0u8; 255 + 2];
+    stream.read_exact(&mut bound[.. addr_len + 2])?;
+
+    Ok(())
+}
@@ -0,0 +1,378 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{
+        Hash,
+        Hasher,
+    },
+    net::TcpStream,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        Arc,
+        Mutex,
+        OnceLock,
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    },
+};
+
+use openssl::{
+    error::ErrorStack,
+    hash::{
+        hash,
+        MessageDigest,
+    },
+    ex_data::Index,
+    ssl::{
+        Ssl,
+        SslConnector,
+        SslFiletype,
+        SslMethod,
+        SslSession,
+        SslSessionCacheMode,
+        SslStream,
+        SslVerifyMode,
+        SslVersion,
+    },
+    x509::store::X509StoreBuilder,
+};
+
+use super::{
+    HttpStreamError,
+    Result,
+};
+
+
+/// Minimal TLS protocol version
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HttpTlsVersion {
+    TLS10,
+    TLS11,
+    TLS12,
+    TLS13,
+}
+
+
+impl From<HttpTlsVersion> for SslVersion {
+    fn from(v: HttpTlsVersion) -> SslVersion {
+        match v {
+            HttpTlsVersion::TLS10 => SslVersion::TLS1,
+            HttpTlsVersion::TLS11 => SslVersion::TLS1_1,
+            HttpTlsVersion::TLS12 => SslVersion::TLS1_2,
+            HttpTlsVersion::TLS13 => SslVersion::TLS1_3,
+        }
+    }
+}
+
+
+/// TLS configuration for HttpClient
+///
+/// Usage:
+///
+/// ```no_run
+/// use http::{
+///     HttpClient,
+///     HttpTlsConfig,
+///     HttpTlsVersion,
+/// };
+///
+/// let mut tls = HttpTlsConfig::default();
+/// tls.add_ca_file("/etc/ssl/private/ca.pem");
+/// tls.set_certificate("/etc/ssl/private/client.pem", "/etc/ssl/private/client.key");
+/// tls.set_min_version(HttpTlsVersion::TLS12);
+///
+/// let mut client = HttpClient::new("https://encoder.local").unwrap();
+/// client.set_tls(&tls).unwrap();
+/// client.get().unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct HttpTlsConfig {
+    ca_file: Vec<PathBuf>,
+    ca_path: Vec<PathBuf>,
+    no_default_ca: bool,
+    cert_file: Option<PathBuf>,
+    key_file: Option<PathBuf>,
+    min_version: Option<HttpTlsVersion>,
+    cipher_list: Option<String>,
+    insecure: bool,
+    pins: HashMap<String, Vec<String>>,
+}
+
+
+impl HttpTlsConfig {
+    /// Allocates new TLS configuration with system defaults
+    #[inline]
+    pub fn new() -> Self { HttpTlsConfig::default() }
+
+    /// Appends file with trusted CA certificates in PEM format
+    #[inline]
+    pub fn add_ca_file<P: AsRef<Path>>(&mut self, path: P) {
+        self.ca_file.push(path.as_ref().to_owned())
+    }
+
+    /// Appends directory with trusted CA certificates.
+    /// Directory should be prepared with `openssl rehash`
+    #[inline]
+    pub fn add_ca_path<P: AsRef<Path>>(&mut self, path: P) {
+        self.ca_path.push(path.as_ref().to_owned())
+    }
+
+    /// Do not use system CA certificates.
+    /// Only certificates defined with `add_ca_file()` and `add_ca_path()` are trusted
+    #[inline]
+    pub fn set_no_default_ca(&mut self, value: bool) { self.no_default_ca = value }
+
+    /// Sets client certificate chain and private key in PEM format for mutual TLS
+    pub fn set_certificate<P: AsRef<Path>, K: AsRef<Path>>(&mut self, cert: P, key: K) {
+        self.cert_file = Some(cert.as_ref().to_owned());
+        self.key_file = Some(key.as_ref().to_owned());
+    }
+
+    /// Sets minimal protocol version
+    #[inline]
+    pub fn set_min_version(&mut self, version: HttpTlsVersion) { self.min_version = Some(version) }
+
+    /// Sets list of the allowed ciphers in OpenSSL format for TLSv1.2 and below
+    #[inline]
+    pub fn set_cipher_list(&mut self, list: &str) { self.cipher_list = Some(list.to_owned()) }
+
+    /// Accepts invalid certificates and host names.
+    /// Connection is not protected against MITM attack, use only for trusted devices
+    #[inline]
+    pub fn set_insecure(&mut self, value: bool) { self.insecure = value }
+
+    /// Pins public key for the host.
+    /// `pin` is a base64 encoded SHA-256 hash of the SubjectPublicKeyInfo,
+    /// optionally with `sha256//` prefix.
+    /// Connection fails if peer certificate chain does not contain any pinned key.
+    /// Could be defined multiple times for backup keys
+    ///
+    /// Pin could be obtained with:
+    ///
+    /// ```text
+    /// openssl x509 -in cert.pem -pubkey -noout \
+    ///     | openssl pkey -pubin -outform der \
+    ///     | openssl dgst -sha256 -binary \
+    ///     | base64
+    /// ```
+    pub fn add_pin(&mut self, host: &str, pin: &str) {
+        let pin = pin.trim();
+        let pin = pin.strip_prefix("sha256//").unwrap_or(pin);
+        self.pins
+            .entry(host.to_ascii_lowercase())
+            .or_default()
+            .push(pin.to_owned())
+    }
+
+    fn build(&self, sessions: &HttpTlsSessions) -> std::result::Result<SslConnector, ErrorStack> {
+        let mut builder = SslConnector::builder(SslMethod::tls())?;
+
+        // sessions stored by callback because TLSv1.3 tickets received after handshake
+        let index = session_index()?;
+        let sessions = sessions.clone();
+        builder.set_session_cache_mode(SslSessionCacheMode::CLIENT);
+        builder.set_new_session_callback(move |ssl, session| {
+            if let Some(key) = ssl.ex_data(index) {
+                sessions.lock().unwrap().insert(key.clone(), session);
+            }
+        });
+
+        if self.no_default_ca {
+            builder.set_cert_store(X509StoreBuilder::new()?.build());
+        }
+
+        for path in &self.ca_file {
+            builder.load_verify_locations(Some(path), None)?;
+        }
+
+        for path in &self.ca_path {
+            builder.load_verify_locations(None, Some(path))?;
+        }
+
+        if let Some(path) = &self.cert_file {
+            builder.set_certificate_chain_file(path)?;
+        }
+
+        if let Some(path) = &self.key_file {
+            builder.set_private_key_file(path, SslFiletype::PEM)?;
+            builder.check_private_key()?;
+        }
+
+        if let Some(version) = self.min_version {
+            builder.set_min_proto_version(Some(version.into()))?;
+        }
+
+        if let Some(list) = &self.cipher_list {
+            builder.set_cipher_list(list)?;
+        }
+
+        if self.insecure {
+            builder.set_verify(SslVerifyMode::NONE);
+        }
+
+        Ok(builder.build())
+    }
+}
+
+
+/// Checks that peer certificate chain contains any of pinned public keys
+fn check_pins(stream: &SslStream<TcpStream>, pins: &[String]) -> Result<bool> {
+    if let Some(chain) = stream.ssl().peer_cert_chain() {
+        for cert in chain {
+            let spki = cert.public_key()?.public_key_to_der()?;
+            let pin = base64::encode(&hash(MessageDigest::sha256(), &spki)?);
+            if pins.contains(&pin) {
+                return Ok(true)
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+
+/// TLS sessions for resumption by `host:port`
+type HttpTlsSessions = Arc<Mutex<HashMap<String, SslSession>>>;
+
+
+/// Index of the session key in the Ssl object
+fn session_index() -> std::result::Result<Index<Ssl, String>, ErrorStack> {
+    static INDEX: OnceLock<Index<Ssl, String>> = OnceLock::new();
+
+    if let Some(index) = INDEX.get() {
+        return Ok(*index)
+    }
+
+    let index = Ssl::new_ex_index()?;
+    Ok(*INDEX.get_or_init(|| index))
+}
+
+
+struct HttpTlsInner {
+    id: usize,
+    connector: SslConnector,
+    sessions: HttpTlsSessions,
+    insecure: bool,
+    pins: HashMap<String, Vec<String>>,
+}
+
+
+/// Prepared TLS context shared between connections
+///
+/// Usage:
+///
+/// ```no_run
+/// use http::{
+///     HttpClient,
+///     HttpTls,
+///     HttpTlsConfig,
+/// };
+///
+/// let mut config = HttpTlsConfig::default();
+/// config.add_ca_file("/etc/ssl/private/ca.pem");
+/// let tls = HttpTls::new(&config).unwrap();
+///
+/// for url in &["https://encoder-1.local", "https://encoder-2.local"] {
+///     let mut client = HttpClient::new(*url).unwrap();
+///     client.set_tls_context(&tls);
+///     client.get().unwrap();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct HttpTls {
+    inner: Arc<HttpTlsInner>,
+}
+
+
+impl fmt::Debug for HttpTls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HttpTls")
+            .field("id", &self.inner.id)
+            .field("insecure", &self.inner.insecure)
+            .finish()
+    }
+}
+
+
+/// Identifies TLS context in the connection pool.
+/// Connection could be reused only with the same TLS context
+impl Hash for HttpTls {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.inner.id.hash(state) }
+}
+
+
+impl PartialEq for HttpTls {
+    fn eq(&self, other: &HttpTls) -> bool { self.inner.id == other.inner.id }
+}
+
+
+impl Eq for HttpTls {}
+
+
+impl HttpTls {
+    /// Prepares TLS context.
+    /// Context could be shared between clients with `HttpClient::set_tls_context()`
+    pub fn new(config: &HttpTlsConfig) -> std::result::Result<Self, ErrorStack> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+        let sessions = HttpTlsSessions::default();
+
+        Ok(HttpTls {
+            inner: Arc::new(HttpTlsInner {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                connector: config.build(&sessions)?,
+                sessions,
+                insecure: config.insecure,
+                pins: config.pins.clone(),
+            }),
+        })
+    }
+
+    /// Returns shared TLS context with system defaults
+    pub (crate) fn global() -> Result<Self> {
+        static GLOBAL: OnceLock<HttpTls> = OnceLock::new();
+
+        if let Some(tls) = GLOBAL.get() {
+            return Ok(tls.clone())
+        }
+
+        let tls = HttpTls::new(&HttpTlsConfig::default())?;
+        Ok(GLOBAL.get_or_init(|| tls).clone())
+    }
+
+    /// TLS handshake. Resumes previous session with the same host and port
+    pub (crate) fn connect(&self, host: &str, port: u16, stream: TcpStream) -> Result<SslStream<TcpStream>> {
+        let mut ssl = self.inner.connector.configure()?;
+        ssl.set_use_server_name_indication(true);
+        ssl.set_verify_hostname(! self.inner.insecure);
+
+        let key = format!("{}:{}", host.to_ascii_lowercase(), port);
+        if let Some(session) = self.inner.sessions.lock().unwrap().get(&key) {
+            // session created by the same connector
+            unsafe { ssl.set_session(session)? };
+        }
+        ssl.set_ex_data(session_index()?, key);
+
+        let stream = ssl.connect(host, stream)?;
+
+        if let Some(pins) = self.inner.pins.get(&host.to_ascii_lowercase()) {
+            if ! check_pins(&stream, pins)? {
+                return Err(HttpStreamError::PinMismatch(host.to_owned()))
+            }
+        }
+
+        Ok(stream)
+    }
+}
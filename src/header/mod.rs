@@ -11,24 +11,22 @@ use std::{
         self,
         Write,
     },
-    collections::HashMap,
 };
 
-mod key;
-pub use self::key::HeaderKey;
-
 mod pair;
 use self::pair::HeaderPair;
 
 
-/// Set of the headers for HTTP request and response
+/// Set of the headers for HTTP request and response.
+/// Keeps insertion order and all values of the repeated headers.
+/// Header names are case insensitive
 #[derive(Default)]
-pub struct Header(HashMap<HeaderKey, HeaderPair>);
+pub struct Header(Vec<HeaderPair>);
 
 
 impl fmt::Debug for Header {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_list().entries(self.0.values()).finish()
+        f.debug_list().entries(self.0.iter()).finish()
     }
 }
 
@@ -40,42 +38,106 @@ impl Header {
             let key = line[.. skip].trim_end();
             if ! key.is_empty() {
                 let value = line[skip + 1 ..].trim_start();
-                self.0.insert(key.into(), HeaderPair::new(key, value));
+                self.append(key, value);
             }
         }
     }
 
-    /// Writes header key and value into dst
-    pub fn send<W: Write>(&self, dst: &mut W) -> io::Result<()> {
-        for (_, pair) in self.0.iter() {
+    /// Writes header key and value into dst in order of insertion
+    pub fn send<W: Write + ?Sized>(&self, dst: &mut W) -> io::Result<()> {
+        for pair in self.0.iter() {
             write!(dst, "{}", pair)?;
         }
 
         Ok(())
     }
 
-    /// Sets header value
-    #[inline]
+    /// Sets header value.
+    /// Replaces all previous values with the same key,
+    /// header keeps position of the first value
     pub fn set<K, V>(&mut self, key: K, val: V)
     where
         K: Into<String>,
         V: ToString,
     {
-        let key = key.into();
-        let hash = key.as_bytes().into();
-        self.0.insert(hash, HeaderPair::new(key, val));
+        let pair = HeaderPair::new(key, val);
+
+        let position = match self.0.iter().position(|v| v.is_key(pair.get_key())) {
+            Some(v) => v,
+            None => return self.0.push(pair),
+        };
+
+        let mut i = position + 1;
+        while i < self.0.len() {
+            if self.0[i].is_key(pair.get_key()) {
+                self.0.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        self.0[position] = pair;
+    }
+
+    /// Appends header value. Keeps previous values with the same key
+    #[inline]
+    pub fn append<K, V>(&mut self, key: K, val: V)
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        self.0.push(HeaderPair::new(key, val))
     }
 
     /// Returns reference to the header value value corresponding to the key
+    /// If header defined multiple times returns first value.
+    /// Use `get_all()` for all values
     /// Key is case insensitive
     #[inline]
     pub fn get<K>(&self, key: K) -> Option<&str>
     where
-        K: Into<HeaderKey>,
+        K: AsRef<str>,
+    {
+        let key = key.as_ref();
+        self.0.iter().find(|v| v.is_key(key)).map(|v| v.get_value())
+    }
+
+    /// Returns all values corresponding to the key in order of insertion
+    /// Key is case insensitive
+    pub fn get_all<'a, K>(&'a self, key: K) -> impl Iterator<Item = &'a str> + 'a
+    where
+        K: AsRef<str> + 'a,
+    {
+        self.0.iter()
+            .filter(move |v| v.is_key(key.as_ref()))
+            .map(|v| v.get_value())
+    }
+
+    /// Removes all values of the header
+    /// Key is case insensitive
+    #[inline]
+    pub fn remove<K>(&mut self, key: K)
+    where
+        K: AsRef<str>,
     {
-        self.0.get(&key.into()).map(|pair| pair.get_value())
+        let key = key.as_ref();
+        self.0.retain(|v| ! v.is_key(key));
+    }
+
+    /// Returns iterator over all header names and values in order of insertion
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|v| (v.get_key(), v.get_value()))
     }
 
+    /// Returns number of the header values
+    #[inline]
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Returns true if header is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
     /// Removes all headers
     #[inline]
     pub fn clear(&mut self) { self.0.clear() }
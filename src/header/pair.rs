@@ -34,6 +34,13 @@ impl HeaderPair {
         }
     }
 
+    /// Compares header name case insensitive
+    #[inline]
+    pub fn is_key(&self, key: &str) -> bool { self.key.eq_ignore_ascii_case(key) }
+
+    #[inline]
+    pub fn get_key(&self) -> &str { self.key.as_str() }
+
     #[inline]
     pub fn get_value(&self) -> &str { self.val.as_str() }
 }
@@ -26,13 +26,50 @@ pub use crate::response::{
     ResponseError,
 };
 
+mod multipart;
+pub use crate::multipart::{
+    Multipart,
+    MultipartPart,
+    MultipartReader,
+};
+
 mod client;
 pub use crate::client::{
+    Authenticator,
+    BasicAuth,
+    BearerAuth,
+    DigestAuth,
+    HttpAuthContext,
+    HttpChallenge,
     HttpClient,
     HttpClientError,
+    HttpCookieJar,
+    HttpPool,
+    HttpTls,
+    HttpTlsConfig,
+    HttpTlsVersion,
     USER_AGENT,
 };
 
+mod sdp;
+pub use crate::sdp::{
+    Sdp,
+    SdpAttribute,
+    SdpConnection,
+    SdpError,
+    SdpMedia,
+    SdpOrigin,
+    SdpRtpMap,
+    SdpTime,
+};
+
+mod rtsp;
+pub use crate::rtsp::{
+    RtspError,
+    RtspSession,
+    RtspTransport,
+};
+
 mod url;
 pub use crate::url::{
     Url,
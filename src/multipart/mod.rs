@@ -0,0 +1,235 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    fmt,
+    io::{
+        self,
+        Read,
+        Write,
+    },
+};
+
+use openssl::rand::rand_bytes;
+
+use crate::Header;
+
+mod reader;
+pub use self::reader::{
+    MultipartReader,
+    MultipartPart,
+};
+
+
+/// Escapes field name or file name for quoted-string in Content-Disposition.
+/// Same as HTML5 form encoding: `"` and line breaks are percent-encoded
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("%22"),
+            '\r' => result.push_str("%0D"),
+            '\n' => result.push_str("%0A"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+
+enum MultipartBody {
+    Data(Vec<u8>),
+    Reader(Box<dyn Read + Send>, Option<u64>),
+}
+
+
+impl fmt::Debug for MultipartBody {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MultipartBody::Data(v) => write!(f, "Data({})", v.len()),
+            MultipartBody::Reader(_, v) => write!(f, "Reader({:?})", v),
+        }
+    }
+}
+
+
+#[derive(Debug)]
+struct MultipartField {
+    /// formatted part headers with trailing empty line
+    header: Vec<u8>,
+    body: MultipartBody,
+}
+
+
+/// Builder for `multipart/form-data` request body (RFC 7578)
+///
+/// Parts are streamed into the writer without buffering of the file content.
+///
+/// Usage:
+///
+/// ```no_run
+/// use std::fs::File;
+/// use http::{
+///     HttpClient,
+///     Multipart,
+/// };
+///
+/// let file = File::open("/tmp/firmware.bin").unwrap();
+/// let len = file.metadata().unwrap().len();
+///
+/// let mut form = Multipart::new();
+/// form.add_text("version", "1.2.3");
+/// form.add_file("image", "firmware.bin", "application/octet-stream", file, Some(len));
+///
+/// let mut client = HttpClient::new("http://encoder.local/upgrade").unwrap();
+/// client.request.set_method("POST");
+/// client.send_multipart(form).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<MultipartField>,
+}
+
+
+impl Default for Multipart {
+    fn default() -> Self {
+        let mut buf = [0; 12];
+        rand_bytes(&mut buf).unwrap();
+
+        Multipart {
+            boundary: format!("------------------------{}", base64::encode_config(&buf, base64::URL_SAFE)),
+            parts: Vec::new(),
+        }
+    }
+}
+
+
+impl Multipart {
+    /// Allocates new builder with random boundary
+    #[inline]
+    pub fn new() -> Self { Multipart::default() }
+
+    /// Returns boundary delimiter
+    #[inline]
+    pub fn get_boundary(&self) -> &str { &self.boundary }
+
+    /// Returns boundary from the Content-Type header value,
+    /// e.g. `multipart/x-mixed-replace; boundary=frame`
+    pub fn parse_boundary(content_type: &str) -> Option<String> {
+        let mut i = content_type.split(';');
+        if ! i.next()?.trim().to_ascii_lowercase().starts_with("multipart/") {
+            return None
+        }
+
+        for param in i {
+            let mut param = param.splitn(2, '=');
+            let name = param.next().unwrap_or("").trim();
+            if name.eq_ignore_ascii_case("boundary") {
+                let value = param.next()?.trim().trim_matches('"');
+                if value.is_empty() {
+                    return None
+                }
+                return Some(value.to_owned())
+            }
+        }
+
+        None
+    }
+
+    /// Returns value for the Content-Type header
+    #[inline]
+    pub fn get_content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", &self.boundary)
+    }
+
+    fn push(&mut self, name: &str, filename: Option<&str>, content_type: Option<&str>, body: MultipartBody) {
+        let mut disposition = format!("form-data; name=\"{}\"", escape(name));
+        if let Some(filename) = filename {
+            disposition.push_str(&format!("; filename=\"{}\"", escape(filename)));
+        }
+
+        let mut header = Header::default();
+        header.set("Content-Disposition", disposition);
+        if let Some(content_type) = content_type {
+            header.set("Content-Type", content_type);
+        }
+
+        let mut buf = Vec::with_capacity(256);
+        header.send(&mut buf).unwrap();
+        buf.extend_from_slice(b"\r\n");
+
+        self.parts.push(MultipartField {
+            header: buf,
+            body,
+        })
+    }
+
+    /// Appends text field
+    pub fn add_text(&mut self, name: &str, value: &str) {
+        self.push(name, None, None, MultipartBody::Data(value.as_bytes().to_vec()))
+    }
+
+    /// Appends file from memory
+    pub fn add_data<D: Into<Vec<u8>>>(&mut self, name: &str, filename: &str, content_type: &str, data: D) {
+        self.push(name, Some(filename), Some(content_type), MultipartBody::Data(data.into()))
+    }
+
+    /// Appends file from any reader.
+    /// `len` is a size of the file content. If size is not defined
+    /// then request sends with chunked transfer encoding
+    pub fn add_file<R>(&mut self, name: &str, filename: &str, content_type: &str, reader: R, len: Option<u64>)
+    where
+        R: Read + Send + 'static,
+    {
+        self.push(name, Some(filename), Some(content_type), MultipartBody::Reader(Box::new(reader), len))
+    }
+
+    /// Returns total length of the body if size of each part is known
+    pub fn get_length(&self) -> Option<u64> {
+        // --boundary\r\n ... \r\n for each part and --boundary--\r\n
+        let delimiter = self.boundary.len() as u64 + 4;
+        let mut total = delimiter + 2;
+
+        for part in &self.parts {
+            let len = match &part.body {
+                MultipartBody::Data(v) => v.len() as u64,
+                MultipartBody::Reader(_, v) => (*v)?,
+            };
+            total += delimiter + part.header.len() as u64 + len + 2;
+        }
+
+        Some(total)
+    }
+
+    /// Writes complete body. Returns error if file size
+    /// is not the same as defined in `add_file()`
+    pub fn send<W: Write>(self, dst: &mut W) -> io::Result<()> {
+        for part in self.parts {
+            write!(dst, "--{}\r\n", &self.boundary)?;
+            dst.write_all(&part.header)?;
+
+            match part.body {
+                MultipartBody::Data(v) => dst.write_all(&v)?,
+                MultipartBody::Reader(reader, None) => {
+                    io::copy(&mut { reader }, dst)?;
+                }
+                MultipartBody::Reader(reader, Some(len)) => {
+                    let n = io::copy(&mut reader.take(len), dst)?;
+                    if n != len {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                            "multipart: file is shorter than defined length"));
+                    }
+                }
+            }
+
+            dst.write_all(b"\r\n")?;
+        }
+
+        write!(dst, "--{}--\r\n", &self.boundary)
+    }
+}
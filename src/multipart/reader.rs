@@ -0,0 +1,239 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::{
+    cmp,
+    io::{
+        self,
+        BufRead,
+        Read,
+    },
+};
+
+use crate::Header;
+
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+
+/// Returns position of the first occurrence of the needle
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|v| v == needle)
+}
+
+
+#[derive(Debug, PartialEq)]
+enum ReaderState {
+    /// reading part body or preamble
+    Body,
+    /// delimiter found, reading part headers
+    Header,
+    /// close delimiter found
+    End,
+}
+
+
+/// Streaming parser for multipart body (RFC 2046 5.1):
+/// `multipart/form-data`, `multipart/byteranges`, `multipart/x-mixed-replace`
+///
+/// Parts are not buffered, so reader could be used for endless streams,
+/// e.g. MJPEG from IP cameras.
+///
+/// Usage:
+///
+/// ```no_run
+/// use std::io::Read;
+/// use http::{
+///     HttpClient,
+///     Multipart,
+///     MultipartReader,
+/// };
+///
+/// let mut client = HttpClient::new("http://camera.local/mjpeg").unwrap();
+/// client.get().unwrap();
+///
+/// let content_type = client.response.header.get("content-type").unwrap_or("");
+/// let boundary = Multipart::parse_boundary(content_type).unwrap();
+///
+/// let mut reader = MultipartReader::new(&mut client, &boundary);
+/// while let Some(mut part) = reader.next_part().unwrap() {
+///     let mut frame = Vec::new();
+///     part.read_to_end(&mut frame).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MultipartReader<R: BufRead> {
+    inner: R,
+    /// `\r\n--boundary`
+    delimiter: Vec<u8>,
+    state: ReaderState,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+
+impl<R: BufRead> MultipartReader<R> {
+    /// Allocates new reader with boundary from the Content-Type header
+    pub fn new(inner: R, boundary: &str) -> Self {
+        let mut delimiter = Vec::with_capacity(boundary.len() + 4);
+        delimiter.extend_from_slice(b"\r\n--");
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        let mut buf = Vec::with_capacity(BUFFER_SIZE);
+        // first delimiter could be at the beginning of the body without line break
+        buf.extend_from_slice(b"\r\n");
+
+        MultipartReader {
+            inner,
+            delimiter,
+            state: ReaderState::Body,
+            buf,
+            pos: 0,
+        }
+    }
+
+    /// Reads more data into the buffer. Returns false on end of stream
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.pos != 0 {
+            self.buf.drain(.. self.pos);
+            self.pos = 0;
+        }
+
+        if self.buf.len() >= BUFFER_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "multipart: line too long"))
+        }
+
+        let data = self.inner.fill_buf()?;
+        if data.is_empty() {
+            return Ok(false)
+        }
+
+        let len = cmp::min(data.len(), BUFFER_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[.. len]);
+        self.inner.consume(len);
+        Ok(true)
+    }
+
+    /// Reads line from the buffer without line break
+    fn read_line(&mut self) -> io::Result<String> {
+        loop {
+            if let Some(len) = self.buf[self.pos ..].iter().position(|b| *b == b'\n') {
+                let line = String::from_utf8_lossy(&self.buf[self.pos .. self.pos + len]).into_owned();
+                self.pos += len + 1;
+                return Ok(line)
+            }
+
+            if ! self.fill()? {
+                return Err(io::ErrorKind::UnexpectedEof.into())
+            }
+        }
+    }
+
+    /// Skips delimiter and checks for close delimiter
+    fn skip_delimiter(&mut self) -> io::Result<()> {
+        self.pos += self.delimiter.len();
+
+        while self.buf.len() - self.pos < 2 {
+            if ! self.fill()? {
+                return Err(io::ErrorKind::UnexpectedEof.into())
+            }
+        }
+
+        if &self.buf[self.pos .. self.pos + 2] == b"--" {
+            self.state = ReaderState::End;
+        } else {
+            // rest of the line is a transport padding
+            self.read_line()?;
+            self.state = ReaderState::Header;
+        }
+
+        Ok(())
+    }
+
+    /// Reads body of the current part until delimiter
+    fn read_body(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.state != ReaderState::Body || out.is_empty() {
+            return Ok(0)
+        }
+
+        loop {
+            let data = &self.buf[self.pos ..];
+
+            // data before delimiter or before possible beginning of delimiter
+            let len = match find(data, &self.delimiter) {
+                Some(0) => {
+                    self.skip_delimiter()?;
+                    return Ok(0)
+                }
+                Some(v) => v,
+                None => data.len().saturating_sub(self.delimiter.len() - 1),
+            };
+
+            if len != 0 {
+                let len = cmp::min(len, out.len());
+                out[.. len].copy_from_slice(&data[.. len]);
+                self.pos += len;
+                return Ok(len)
+            }
+
+            if ! self.fill()? {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof,
+                    "multipart: close delimiter not found"))
+            }
+        }
+    }
+
+    /// Skips rest of the current part and reads headers of the next part.
+    /// Returns None after close delimiter
+    pub fn next_part(&mut self) -> io::Result<Option<MultipartPart<'_, R>>> {
+        if self.state == ReaderState::Body {
+            let mut skip = [0; 1024];
+            while self.read_body(&mut skip)? != 0 {}
+        }
+
+        if self.state == ReaderState::End {
+            return Ok(None)
+        }
+
+        let mut header = Header::default();
+        loop {
+            let line = self.read_line()?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break
+            }
+            header.parse(line);
+        }
+
+        self.state = ReaderState::Body;
+
+        Ok(Some(MultipartPart {
+            header,
+            reader: self,
+        }))
+    }
+
+    /// Returns inner reader. Data prefetched into the buffer is dropped
+    #[inline]
+    pub fn into_inner(self) -> R { self.inner }
+}
+
+
+/// Part of the multipart body.
+/// Body reads until next delimiter
+#[derive(Debug)]
+pub struct MultipartPart<'a, R: BufRead> {
+    /// part headers
+    pub header: Header,
+    reader: &'a mut MultipartReader<R>,
+}
+
+
+impl<'a, R: BufRead> Read for MultipartPart<'a, R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.reader.read_body(buf) }
+}
@@ -42,7 +42,8 @@ pub struct Request {
     pub url: Url,
     version: HttpVersion,
     pub header: Header,
-    pub (crate) nonce_count: usize
+    /// request-target in absolute-form for HTTP proxy
+    pub (crate) absolute_form: bool,
 }
 
 
@@ -53,7 +54,7 @@ impl Default for Request {
             url: Url::default(),
             version: HttpVersion::default(),
             header: Header::default(),
-            nonce_count: 0,
+            absolute_form: false,
         }
     }
 }
@@ -85,10 +86,10 @@ impl Request {
             if first_line {
                 first_line = false;
 
-                let skip = s.find(char::is_whitespace).ok_or_else(|| RequestError::InvalidFormat)?;
+                let skip = s.find(char::is_whitespace).ok_or(RequestError::InvalidFormat)?;
                 self.method.push_str(&s[.. skip]);
                 let s = s[skip + 1 ..].trim_start();
-                let skip = s.find(char::is_whitespace).unwrap_or_else(|| s.len());
+                let skip = s.find(char::is_whitespace).unwrap_or(s.len());
                 self.url.set(&s[.. skip])?;
 
                 if s.len() > skip {
@@ -107,8 +108,8 @@ impl Request {
 
     fn io_send<W: Write>(&self, dst: &mut W) -> io::Result<()> {
         let path_fmt = match self.version {
-            HttpVersion::HTTP11 | HttpVersion::HTTP10 => UrlFormatter::RequestUri(&self.url),
-            HttpVersion::RTSP10 | HttpVersion::RTSP20 => UrlFormatter::Url(&self.url),
+            HttpVersion::HTTP11 | HttpVersion::HTTP10 if ! self.absolute_form => UrlFormatter::RequestUri(&self.url),
+            _ => UrlFormatter::Url(&self.url),
         };
 
         writeln!(dst,"{} {} {}\r",
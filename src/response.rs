@@ -33,28 +33,16 @@ pub type Result<T> = std::result::Result<T, ResponseError>;
 
 
 /// Parser and formatter for HTTP response line and headers
-#[derive(Debug)]
+#[derive(Default, Debug)]
 pub struct Response {
     version: HttpVersion,
     code: usize,
     reason: String,
-    ///
+    /// response headers
     pub header: Header,
 }
 
 
-impl Default for Response {
-    fn default() -> Response {
-        Response {
-            version: HttpVersion::default(),
-            code: 0,
-            reason: String::default(),
-            header: Header::default(),
-        }
-    }
-}
-
-
 impl Response {
     /// Allocates new request object
     #[inline]
@@ -82,10 +70,10 @@ impl Response {
             if first_line {
                 first_line = false;
 
-                let skip = s.find(char::is_whitespace).ok_or_else(|| ResponseError::InvalidFormat)?;
+                let skip = s.find(char::is_whitespace).ok_or(ResponseError::InvalidFormat)?;
                 self.version = s[.. skip].into();
                 let s = s[skip + 1 ..].trim_start();
-                let skip = s.find(char::is_whitespace).unwrap_or_else(|| s.len());
+                let skip = s.find(char::is_whitespace).unwrap_or(s.len());
                 self.code = s[.. skip].parse().unwrap_or(0);
                 if self.code < 100 || self.code >= 600 { return Err(ResponseError::InvalidStatus) }
 
@@ -103,7 +91,7 @@ impl Response {
         Ok(())
     }
 
-    fn io_send<W: Write>(&self, dst: &mut W) -> io::Result<()> {
+    fn io_send<W: Write + ?Sized>(&self, dst: &mut W) -> io::Result<()> {
         writeln!(dst, "{} {} {}\r",
             self.version,
             self.code,
@@ -116,7 +104,7 @@ impl Response {
 
     /// Writes response line and headers to dst
     #[inline]
-    pub fn send<W: Write>(&self, dst: &mut W) -> Result<()> {
+    pub fn send<W: Write + ?Sized>(&self, dst: &mut W) -> Result<()> {
         self.io_send(dst)?;
         Ok(())
     }
@@ -0,0 +1,45 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::io;
+
+use crate::{
+    HttpClientError,
+    SdpError,
+    UrlError,
+};
+
+mod session;
+pub use self::session::RtspSession;
+
+mod transport;
+pub use self::transport::RtspTransport;
+
+
+#[derive(Debug, Error)]
+#[error_prefix = "Rtsp"]
+pub enum RtspError {
+    #[error_from]
+    Io(io::Error),
+    #[error_from]
+    HttpClient(HttpClientError),
+    #[error_from]
+    Url(UrlError),
+    #[error_from]
+    Sdp(SdpError),
+    #[error_kind("CSeq mismatch: expected {} got {}", 0, 1)]
+    CSeqMismatch(usize, String),
+    #[error_kind("session not defined")]
+    NoSession,
+    #[error_kind("unsupported transport")]
+    UnsupportedTransport,
+    #[error_kind("invalid transport")]
+    InvalidTransport,
+}
+
+
+pub type Result<T> = std::result::Result<T, RtspError>;
@@ -0,0 +1,260 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+
+/// Splits value by separator. Separators in quoted strings are skipped
+fn split_quoted(value: &str, sep: char) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut quoted = false;
+    let mut skip = 0;
+
+    for (idx, c) in value.char_indices() {
+        if c == '"' {
+            quoted = ! quoted;
+        } else if c == sep && ! quoted {
+            result.push(&value[skip .. idx]);
+            skip = idx + 1;
+        }
+    }
+
+    result.push(&value[skip ..]);
+    result
+}
+
+
+/// Parses range: `5000-5001` or `5000`
+fn parse_range<T: std::str::FromStr + Copy>(value: &str) -> Option<(T, T)> {
+    let mut i = value.splitn(2, '-');
+    let first = i.next()?.trim().parse().ok()?;
+    let last = match i.next() {
+        Some(v) => v.trim().parse().ok()?,
+        None => first,
+    };
+    Some((first, last))
+}
+
+
+/// Formats range. Single value if first is equal to last
+fn fmt_range<T: fmt::Display + PartialEq>(f: &mut fmt::Formatter, name: &str, value: &(T, T)) -> fmt::Result {
+    if value.0 == value.1 {
+        write!(f, ";{}={}", name, value.0)
+    } else {
+        write!(f, ";{}={}-{}", name, value.0, value.1)
+    }
+}
+
+
+/// RTSP Transport header (RFC 2326 12.39)
+///
+/// Usage:
+///
+/// ```
+/// use http::RtspTransport;
+///
+/// let udp = RtspTransport::udp(5000);
+/// assert_eq!(udp.to_string(), "RTP/AVP;unicast;client_port=5000-5001");
+///
+/// let tcp = RtspTransport::parse("RTP/AVP/TCP;unicast;interleaved=0-1;ssrc=1A2B3C4D").unwrap();
+/// assert_eq!(tcp.interleaved, Some((0, 1)));
+/// assert_eq!(tcp.ssrc, Some(0x1A2B_3C4D));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RtspTransport {
+    /// transport protocol and profile, e.g. `RTP/AVP`
+    pub profile: String,
+    /// lower transport is TCP
+    pub tcp: bool,
+    /// multicast delivery, otherwise unicast
+    pub multicast: bool,
+    /// destination address. Empty string if address is not defined
+    pub destination: Option<String>,
+    /// source address
+    pub source: Option<String>,
+    /// channels for TCP interleaved data
+    pub interleaved: Option<(u8, u8)>,
+    /// append to the existing resource for RECORD
+    pub append: bool,
+    /// multicast time-to-live
+    pub ttl: Option<u8>,
+    /// number of multicast layers
+    pub layers: Option<u32>,
+    /// multicast ports
+    pub port: Option<(u16, u16)>,
+    /// client ports for RTP and RTCP
+    pub client_port: Option<(u16, u16)>,
+    /// server ports for RTP and RTCP
+    pub server_port: Option<(u16, u16)>,
+    /// RTP synchronization source
+    pub ssrc: Option<u32>,
+    /// method for the transport: `PLAY` or `RECORD`
+    pub mode: Option<String>,
+    /// unknown parameters
+    pub params: Vec<(String, Option<String>)>,
+}
+
+
+impl Default for RtspTransport {
+    fn default() -> Self {
+        RtspTransport {
+            profile: "RTP/AVP".to_owned(),
+            tcp: false,
+            multicast: false,
+            destination: None,
+            source: None,
+            interleaved: None,
+            append: false,
+            ttl: None,
+            layers: None,
+            port: None,
+            client_port: None,
+            server_port: None,
+            ssrc: None,
+            mode: None,
+            params: Vec::new(),
+        }
+    }
+}
+
+
+impl RtspTransport {
+    /// Unicast RTP over UDP. RTCP port is `port + 1`
+    pub fn udp(port: u16) -> Self {
+        RtspTransport {
+            client_port: Some((port, port.saturating_add(1))),
+            ..RtspTransport::default()
+        }
+    }
+
+    /// Unicast RTP interleaved into RTSP connection.
+    /// RTCP channel is `channel + 1`
+    pub fn tcp(channel: u8) -> Self {
+        RtspTransport {
+            tcp: true,
+            interleaved: Some((channel, channel.saturating_add(1))),
+            ..RtspTransport::default()
+        }
+    }
+
+    /// Parses single transport specification
+    pub fn parse(value: &str) -> Option<Self> {
+        let params = split_quoted(value.trim(), ';');
+        let mut params = params.iter().map(|v| v.trim());
+
+        let protocol = params.next().filter(|v| ! v.is_empty())?;
+        let mut transport = RtspTransport::default();
+
+        let mut i = protocol.splitn(3, '/');
+        let name = i.next()?;
+        let profile = i.next()?;
+        transport.profile = format!("{}/{}", name, profile);
+        transport.tcp = match i.next() {
+            Some(v) if v.eq_ignore_ascii_case("TCP") => true,
+            Some(v) if v.eq_ignore_ascii_case("UDP") => false,
+            Some(_) => return None,
+            None => false,
+        };
+
+        for param in params.filter(|v| ! v.is_empty()) {
+            let mut i = param.splitn(2, '=');
+            let name = i.next().unwrap_or("").trim().to_ascii_lowercase();
+            let value = i.next().map(|v| v.trim().trim_matches('"'));
+
+            match (name.as_str(), value) {
+                ("unicast", None) => transport.multicast = false,
+                ("multicast", None) => transport.multicast = true,
+                ("append", None) => transport.append = true,
+                ("destination", v) => transport.destination = Some(v.unwrap_or("").to_owned()),
+                ("source", Some(v)) => transport.source = Some(v.to_owned()),
+                ("interleaved", Some(v)) => transport.interleaved = Some(parse_range(v)?),
+                ("ttl", Some(v)) => transport.ttl = Some(v.parse().ok()?),
+                ("layers", Some(v)) => transport.layers = Some(v.parse().ok()?),
+                ("port", Some(v)) => transport.port = Some(parse_range(v)?),
+                ("client_port", Some(v)) => transport.client_port = Some(parse_range(v)?),
+                ("server_port", Some(v)) => transport.server_port = Some(parse_range(v)?),
+                ("ssrc", Some(v)) => transport.ssrc = Some(u32::from_str_radix(v, 16).ok()?),
+                ("mode", Some(v)) => transport.mode = Some(v.to_owned()),
+                (_, v) => transport.params.push((name, v.map(str::to_owned))),
+            }
+        }
+
+        Some(transport)
+    }
+
+    /// Parses comma-separated list of alternative transports.
+    /// Invalid alternatives are skipped
+    pub fn parse_list(value: &str) -> Vec<Self> {
+        split_quoted(value, ',')
+            .into_iter()
+            .filter_map(RtspTransport::parse)
+            .collect()
+    }
+
+    /// Formats list of alternative transports in order of preference
+    pub fn format_list(list: &[Self]) -> String {
+        list.iter()
+            .map(RtspTransport::to_string)
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+
+impl fmt::Display for RtspTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.profile)?;
+        if self.tcp {
+            f.write_str("/TCP")?;
+        }
+        f.write_str(if self.multicast { ";multicast" } else { ";unicast" })?;
+
+        match self.destination.as_deref() {
+            Some("") => f.write_str(";destination")?,
+            Some(v) => write!(f, ";destination={}", v)?,
+            None => {}
+        }
+        if let Some(v) = &self.source {
+            write!(f, ";source={}", v)?;
+        }
+        if let Some(v) = &self.interleaved {
+            fmt_range(f, "interleaved", v)?;
+        }
+        if self.append {
+            f.write_str(";append")?;
+        }
+        if let Some(v) = self.ttl {
+            write!(f, ";ttl={}", v)?;
+        }
+        if let Some(v) = self.layers {
+            write!(f, ";layers={}", v)?;
+        }
+        if let Some(v) = &self.port {
+            fmt_range(f, "port", v)?;
+        }
+        if let Some(v) = &self.client_port {
+            fmt_range(f, "client_port", v)?;
+        }
+        if let Some(v) = &self.server_port {
+            fmt_range(f, "server_port", v)?;
+        }
+        if let Some(v) = self.ssrc {
+            write!(f, ";ssrc={:08X}", v)?;
+        }
+        if let Some(v) = &self.mode {
+            write!(f, ";mode=\"{}\"", v)?;
+        }
+        for (name, value) in &self.params {
+            match value {
+                Some(value) => write!(f, ";{}={}", name, value)?,
+                None => write!(f, ";{}", name)?,
+            }
+        }
+
+        Ok(())
+    }
+}
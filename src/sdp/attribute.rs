@@ -0,0 +1,123 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+
+/// SDP attribute: `a=name:value` or property flag `a=name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdpAttribute {
+    pub name: String,
+    /// None for property attribute, e.g. `a=recvonly`
+    pub value: Option<String>,
+}
+
+
+impl SdpAttribute {
+    /// Parses attribute line value without `a=`
+    pub fn parse(line: &str) -> Self {
+        let mut i = line.splitn(2, ':');
+        SdpAttribute {
+            name: i.next().unwrap_or("").to_owned(),
+            value: i.next().map(str::to_owned),
+        }
+    }
+}
+
+
+impl fmt::Display for SdpAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}:{}", &self.name, value),
+            None => write!(f, "{}", &self.name),
+        }
+    }
+}
+
+
+/// Returns value of the first attribute with given name.
+/// Returns empty string for property attribute
+pub fn get_attribute<'a>(list: &'a [SdpAttribute], name: &str) -> Option<&'a str> {
+    list.iter()
+        .find(|v| v.name == name)
+        .map(|v| v.value.as_deref().unwrap_or(""))
+}
+
+
+/// Returns value of the attribute for given format without format prefix:
+/// `a=fmtp:96 packetization-mode=1` for format `96`
+pub fn get_format_attribute<'a>(list: &'a [SdpAttribute], name: &str, format: &str) -> Option<&'a str> {
+    list.iter()
+        .filter(|v| v.name == name)
+        .filter_map(|v| v.value.as_deref())
+        .find_map(|v| {
+            let mut i = v.splitn(2, ' ');
+            if i.next()? == format {
+                Some(i.next().unwrap_or("").trim())
+            } else {
+                None
+            }
+        })
+}
+
+
+/// Sets value of the first attribute with given name or appends new attribute
+pub fn set_attribute(list: &mut Vec<SdpAttribute>, name: &str, value: &str) {
+    match list.iter_mut().find(|v| v.name == name) {
+        Some(item) => item.value = Some(value.to_owned()),
+        None => list.push(SdpAttribute {
+            name: name.to_owned(),
+            value: Some(value.to_owned()),
+        }),
+    }
+}
+
+
+/// RTP payload mapping: `a=rtpmap:96 H264/90000`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SdpRtpMap {
+    /// RTP payload type
+    pub payload: u8,
+    /// encoding name, e.g. `H264`
+    pub encoding: String,
+    /// clock rate in Hz
+    pub clock_rate: u32,
+    /// encoding parameters, e.g. number of audio channels
+    pub params: String,
+}
+
+
+impl SdpRtpMap {
+    /// Parses rtpmap attribute value
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut i = value.trim().splitn(2, ' ');
+        let payload = i.next()?.parse().ok()?;
+
+        let mut i = i.next()?.trim().splitn(3, '/');
+        let encoding = i.next()?.to_owned();
+        let clock_rate = i.next()?.parse().ok()?;
+        let params = i.next().unwrap_or("").to_owned();
+
+        Some(SdpRtpMap {
+            payload,
+            encoding,
+            clock_rate,
+            params,
+        })
+    }
+}
+
+
+impl fmt::Display for SdpRtpMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}/{}", self.payload, &self.encoding, self.clock_rate)?;
+        if ! self.params.is_empty() {
+            write!(f, "/{}", &self.params)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+use super::{
+    SdpAttribute,
+    SdpConnection,
+    SdpRtpMap,
+    attribute::{
+        get_attribute,
+        get_format_attribute,
+        set_attribute,
+    },
+};
+
+
+/// Media description: `m=` line and media-level fields
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SdpMedia {
+    /// media type: `video`, `audio`, `application`, ...
+    pub media: String,
+    /// transport port. Usually 0 for RTSP
+    pub port: u16,
+    /// number of ports: `m=video 49170/2 ...`
+    pub port_count: Option<u16>,
+    /// transport protocol, e.g. `RTP/AVP`
+    pub proto: String,
+    /// media formats. RTP payload types for RTP/AVP
+    pub formats: Vec<String>,
+    /// media title `i=`
+    pub title: String,
+    /// connection data `c=`
+    pub connection: Option<SdpConnection>,
+    /// bandwidth `b=`
+    pub bandwidth: Vec<String>,
+    /// encryption key `k=`
+    pub key: String,
+    /// media-level attributes `a=`
+    pub attributes: Vec<SdpAttribute>,
+}
+
+
+impl SdpMedia {
+    /// Allocates new media description
+    pub fn new(media: &str, port: u16, proto: &str) -> Self {
+        SdpMedia {
+            media: media.to_owned(),
+            port,
+            proto: proto.to_owned(),
+            ..SdpMedia::default()
+        }
+    }
+
+    /// Parses `m=` line value
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut i = line.split_whitespace();
+
+        let media = i.next()?.to_owned();
+
+        let mut port = i.next()?.splitn(2, '/');
+        let port_count = match port.clone().nth(1) {
+            Some(v) => Some(v.parse().ok()?),
+            None => None,
+        };
+        let port = port.next()?.parse().ok()?;
+
+        let proto = i.next()?.to_owned();
+        let formats = i.map(str::to_owned).collect();
+
+        Some(SdpMedia {
+            media,
+            port,
+            port_count,
+            proto,
+            formats,
+            ..SdpMedia::default()
+        })
+    }
+
+    /// Returns value of the first attribute with given name
+    #[inline]
+    pub fn get_attribute(&self, name: &str) -> Option<&str> { get_attribute(&self.attributes, name) }
+
+    /// Sets value of the attribute
+    #[inline]
+    pub fn set_attribute(&mut self, name: &str, value: &str) { set_attribute(&mut self.attributes, name, value) }
+
+    /// Appends attribute. Attributes with the same name could be repeated
+    pub fn add_attribute(&mut self, name: &str, value: &str) {
+        self.attributes.push(SdpAttribute {
+            name: name.to_owned(),
+            value: Some(value.to_owned()),
+        })
+    }
+
+    /// Appends property attribute, e.g. `recvonly`
+    pub fn add_flag(&mut self, name: &str) {
+        self.attributes.push(SdpAttribute {
+            name: name.to_owned(),
+            value: None,
+        })
+    }
+
+    /// Returns control url of the track. Relative to the content base
+    #[inline]
+    pub fn get_control(&self) -> Option<&str> { self.get_attribute("control") }
+
+    /// Returns media range, e.g. `npt=0-34.5`
+    #[inline]
+    pub fn get_range(&self) -> Option<&str> { self.get_attribute("range") }
+
+    /// Returns RTP payload mapping for the format
+    pub fn get_rtpmap(&self, format: &str) -> Option<SdpRtpMap> {
+        self.attributes.iter()
+            .filter(|v| v.name == "rtpmap")
+            .filter_map(|v| SdpRtpMap::parse(v.value.as_deref()?))
+            .find(|v| v.payload.to_string() == format)
+    }
+
+    /// Appends format and RTP payload mapping
+    pub fn add_rtpmap(&mut self, rtpmap: &SdpRtpMap) {
+        self.formats.push(rtpmap.payload.to_string());
+        self.add_attribute("rtpmap", &rtpmap.to_string());
+    }
+
+    /// Returns format parameters, e.g. `packetization-mode=1;profile-level-id=42e01f`
+    #[inline]
+    pub fn get_fmtp(&self, format: &str) -> Option<&str> {
+        get_format_attribute(&self.attributes, "fmtp", format)
+    }
+
+    /// Returns value of the format parameter
+    pub fn get_fmtp_param(&self, format: &str, name: &str) -> Option<&str> {
+        self.get_fmtp(format)?
+            .split(';')
+            .find_map(|param| {
+                let mut i = param.splitn(2, '=');
+                if i.next()?.trim().eq_ignore_ascii_case(name) {
+                    Some(i.next().unwrap_or("").trim())
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+
+impl fmt::Display for SdpMedia {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m={} {}", &self.media, self.port)?;
+        if let Some(count) = self.port_count {
+            write!(f, "/{}", count)?;
+        }
+        write!(f, " {}", &self.proto)?;
+        for format in &self.formats {
+            write!(f, " {}", format)?;
+        }
+        f.write_str("\r\n")?;
+
+        if ! self.title.is_empty() {
+            write!(f, "i={}\r\n", &self.title)?;
+        }
+        if let Some(connection) = &self.connection {
+            write!(f, "c={}\r\n", connection)?;
+        }
+        for bandwidth in &self.bandwidth {
+            write!(f, "b={}\r\n", bandwidth)?;
+        }
+        if ! self.key.is_empty() {
+            write!(f, "k={}\r\n", &self.key)?;
+        }
+        for attribute in &self.attributes {
+            write!(f, "a={}\r\n", attribute)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,408 @@
+// Copyright (C) 2019 Cesbo OU <info@cesbo.com>
+//
+// This file is part of ASC/libhttp
+//
+// ASC/libhttp can not be copied and/or distributed without the express
+// permission of Cesbo OU
+
+use std::fmt;
+
+mod attribute;
+pub use self::attribute::{
+    SdpAttribute,
+    SdpRtpMap,
+};
+use self::attribute::{
+    get_attribute,
+    set_attribute,
+};
+
+mod media;
+pub use self::media::SdpMedia;
+
+
+#[derive(Debug, Error)]
+#[error_prefix = "Sdp"]
+pub enum SdpError {
+    #[error_kind("invalid format at line {}", 0)]
+    InvalidFormat(usize),
+    #[error_kind("version not supported")]
+    InvalidVersion,
+}
+
+
+pub type Result<T> = std::result::Result<T, SdpError>;
+
+
+/// Session originator `o=`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdpOrigin {
+    /// user name. `-` if not defined
+    pub username: String,
+    pub session_id: String,
+    pub session_version: String,
+    /// network type, `IN`
+    pub net_type: String,
+    /// address type, `IP4` or `IP6`
+    pub addr_type: String,
+    pub address: String,
+}
+
+
+impl Default for SdpOrigin {
+    fn default() -> Self {
+        SdpOrigin {
+            username: "-".to_owned(),
+            session_id: "0".to_owned(),
+            session_version: "0".to_owned(),
+            net_type: "IN".to_owned(),
+            addr_type: "IP4".to_owned(),
+            address: "127.0.0.1".to_owned(),
+        }
+    }
+}
+
+
+impl SdpOrigin {
+    /// Parses `o=` line value
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut i = line.split_whitespace().map(str::to_owned);
+        let origin = SdpOrigin {
+            username: i.next()?,
+            session_id: i.next()?,
+            session_version: i.next()?,
+            net_type: i.next()?,
+            addr_type: i.next()?,
+            address: i.next()?,
+        };
+
+        if i.next().is_some() {
+            return None
+        }
+
+        Some(origin)
+    }
+}
+
+
+impl fmt::Display for SdpOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {} {} {} {}",
+            &self.username,
+            &self.session_id,
+            &self.session_version,
+            &self.net_type,
+            &self.addr_type,
+            &self.address)
+    }
+}
+
+
+/// Connection data `c=`: `IN IP4 224.2.36.42/127/3`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdpConnection {
+    /// network type, `IN`
+    pub net_type: String,
+    /// address type, `IP4` or `IP6`
+    pub addr_type: String,
+    /// unicast or multicast address
+    pub address: String,
+    /// TTL for IP4 multicast address
+    pub ttl: Option<u8>,
+    /// number of multicast addresses
+    pub count: Option<u32>,
+}
+
+
+impl SdpConnection {
+    /// Allocates new connection data for IP4 or IP6 address
+    pub fn new(address: &str) -> Self {
+        SdpConnection {
+            net_type: "IN".to_owned(),
+            addr_type: if address.contains(':') { "IP6" } else { "IP4" }.to_owned(),
+            address: address.to_owned(),
+            ttl: None,
+            count: None,
+        }
+    }
+
+    /// Parses `c=` line value
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut i = line.split_whitespace();
+        let net_type = i.next()?.to_owned();
+        let addr_type = i.next()?.to_owned();
+
+        let mut address = i.next()?.split('/');
+        if i.next().is_some() {
+            return None
+        }
+
+        let mut connection = SdpConnection {
+            net_type,
+            addr_type,
+            address: address.next()?.to_owned(),
+            ttl: None,
+            count: None,
+        };
+
+        // IP6 multicast address has no TTL
+        if connection.addr_type != "IP6" {
+            if let Some(ttl) = address.next() {
+                connection.ttl = Some(ttl.parse().ok()?);
+            }
+        }
+        if let Some(count) = address.next() {
+            connection.count = Some(count.parse().ok()?);
+        }
+        if address.next().is_some() {
+            return None
+        }
+
+        Some(connection)
+    }
+}
+
+
+impl fmt::Display for SdpConnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", &self.net_type, &self.addr_type, &self.address)?;
+        if let Some(ttl) = self.ttl {
+            write!(f, "/{}", ttl)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, "/{}", count)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Session time `t=` with repeat times `r=`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SdpTime {
+    /// start time. NTP timestamp or 0 for permanent session
+    pub start: u64,
+    /// stop time. NTP timestamp or 0 for unbounded session
+    pub stop: u64,
+    /// repeat times `r=`
+    pub repeats: Vec<String>,
+}
+
+
+impl SdpTime {
+    /// Parses `t=` line value
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut i = line.split_whitespace();
+        let start = i.next()?.parse().ok()?;
+        let stop = i.next()?.parse().ok()?;
+        if i.next().is_some() {
+            return None
+        }
+
+        Some(SdpTime {
+            start,
+            stop,
+            repeats: Vec::new(),
+        })
+    }
+}
+
+
+/// Session Description Protocol (RFC 8866)
+///
+/// Parses and formats session description for RTSP DESCRIBE and ANNOUNCE.
+/// Unknown fields are ignored.
+///
+/// Usage:
+///
+/// ```
+/// use http::{
+///     Sdp,
+///     SdpMedia,
+///     SdpRtpMap,
+/// };
+///
+/// let mut sdp = Sdp::new("Camera");
+/// sdp.set_attribute("control", "*");
+///
+/// let mut media = SdpMedia::new("video", 0, "RTP/AVP");
+/// media.add_rtpmap(&SdpRtpMap {
+///     payload: 96,
+///     encoding: "H264".to_owned(),
+///     clock_rate: 90000,
+///     params: String::new(),
+/// });
+/// media.add_attribute("fmtp", "96 packetization-mode=1");
+/// media.add_attribute("control", "trackID=1");
+/// sdp.media.push(media);
+///
+/// let sdp = Sdp::parse(&sdp.to_string()).unwrap();
+/// assert_eq!(sdp.media[0].get_rtpmap("96").unwrap().encoding, "H264");
+/// assert_eq!(sdp.media[0].get_fmtp_param("96", "packetization-mode"), Some("1"));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Sdp {
+    /// protocol version `v=`. Always 0
+    pub version: u32,
+    /// originator `o=`
+    pub origin: SdpOrigin,
+    /// session name `s=`
+    pub name: String,
+    /// session information `i=`
+    pub info: String,
+    /// description uri `u=`
+    pub uri: String,
+    /// email addresses `e=`
+    pub emails: Vec<String>,
+    /// phone numbers `p=`
+    pub phones: Vec<String>,
+    /// session-level connection data `c=`
+    pub connection: Option<SdpConnection>,
+    /// bandwidth `b=`
+    pub bandwidth: Vec<String>,
+    /// session times `t=` and `r=`
+    pub times: Vec<SdpTime>,
+    /// time zone adjustments `z=`
+    pub zone: String,
+    /// encryption key `k=`
+    pub key: String,
+    /// session-level attributes `a=`
+    pub attributes: Vec<SdpAttribute>,
+    /// media descriptions
+    pub media: Vec<SdpMedia>,
+}
+
+
+impl Sdp {
+    /// Allocates new permanent session description
+    pub fn new(name: &str) -> Self {
+        Sdp {
+            name: name.to_owned(),
+            times: vec![SdpTime::default()],
+            ..Sdp::default()
+        }
+    }
+
+    /// Parses session description
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut sdp = Sdp::default();
+
+        for (n, line) in data.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue
+            }
+
+            let err = || SdpError::InvalidFormat(n + 1);
+
+            let b = line.as_bytes();
+            if b.len() < 2 || b[1] != b'=' {
+                return Err(err())
+            }
+            let value = &line[2 ..];
+
+            match (b[0], sdp.media.last_mut()) {
+                (b'm', _) => sdp.media.push(SdpMedia::parse(value).ok_or_else(err)?),
+
+                (b'i', Some(media)) => media.title = value.to_owned(),
+                (b'c', Some(media)) => media.connection = Some(SdpConnection::parse(value).ok_or_else(err)?),
+                (b'b', Some(media)) => media.bandwidth.push(value.to_owned()),
+                (b'k', Some(media)) => media.key = value.to_owned(),
+                (b'a', Some(media)) => media.attributes.push(SdpAttribute::parse(value)),
+                (_, Some(_)) => {}
+
+                (b'v', None) => {
+                    if value.trim() != "0" {
+                        return Err(SdpError::InvalidVersion)
+                    }
+                }
+                (b'o', None) => sdp.origin = SdpOrigin::parse(value).ok_or_else(err)?,
+                (b's', None) => sdp.name = value.to_owned(),
+                (b'i', None) => sdp.info = value.to_owned(),
+                (b'u', None) => sdp.uri = value.to_owned(),
+                (b'e', None) => sdp.emails.push(value.to_owned()),
+                (b'p', None) => sdp.phones.push(value.to_owned()),
+                (b'c', None) => sdp.connection = Some(SdpConnection::parse(value).ok_or_else(err)?),
+                (b'b', None) => sdp.bandwidth.push(value.to_owned()),
+                (b't', None) => sdp.times.push(SdpTime::parse(value).ok_or_else(err)?),
+                (b'r', None) => sdp.times.last_mut().ok_or_else(err)?.repeats.push(value.to_owned()),
+                (b'z', None) => sdp.zone = value.to_owned(),
+                (b'k', None) => sdp.key = value.to_owned(),
+                (b'a', None) => sdp.attributes.push(SdpAttribute::parse(value)),
+                (_, None) => {}
+            }
+        }
+
+        Ok(sdp)
+    }
+
+    /// Returns value of the first session-level attribute with given name
+    #[inline]
+    pub fn get_attribute(&self, name: &str) -> Option<&str> { get_attribute(&self.attributes, name) }
+
+    /// Sets value of the session-level attribute
+    #[inline]
+    pub fn set_attribute(&mut self, name: &str, value: &str) { set_attribute(&mut self.attributes, name, value) }
+
+    /// Returns aggregate control url. Relative to the content base
+    #[inline]
+    pub fn get_control(&self) -> Option<&str> { self.get_attribute("control") }
+
+    /// Returns session range, e.g. `npt=0-34.5`
+    #[inline]
+    pub fn get_range(&self) -> Option<&str> { self.get_attribute("range") }
+
+    /// Returns connection data for the media.
+    /// Media-level connection overrides session-level
+    pub fn get_connection<'a>(&'a self, media: &'a SdpMedia) -> Option<&'a SdpConnection> {
+        media.connection.as_ref().or(self.connection.as_ref())
+    }
+}
+
+
+impl fmt::Display for Sdp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "v={}\r\n", self.version)?;
+        write!(f, "o={}\r\n", &self.origin)?;
+        write!(f, "s={}\r\n", if self.name.is_empty() { " " } else { &self.name })?;
+
+        if ! self.info.is_empty() {
+            write!(f, "i={}\r\n", &self.info)?;
+        }
+        if ! self.uri.is_empty() {
+            write!(f, "u={}\r\n", &self.uri)?;
+        }
+        for email in &self.emails {
+            write!(f, "e={}\r\n", email)?;
+        }
+        for phone in &self.phones {
+            write!(f, "p={}\r\n", phone)?;
+        }
+        if let Some(connection) = &self.connection {
+            write!(f, "c={}\r\n", connection)?;
+        }
+        for bandwidth in &self.bandwidth {
+            write!(f, "b={}\r\n", bandwidth)?;
+        }
+        for time in &self.times {
+            write!(f, "t={} {}\r\n", time.start, time.stop)?;
+            for repeat in &time.repeats {
+                write!(f, "r={}\r\n", repeat)?;
+            }
+        }
+        if ! self.zone.is_empty() {
+            write!(f, "z={}\r\n", &self.zone)?;
+        }
+        if ! self.key.is_empty() {
+            write!(f, "k={}\r\n", &self.key)?;
+        }
+        for attribute in &self.attributes {
+            write!(f, "a={}\r\n", attribute)?;
+        }
+        for media in &self.media {
+            write!(f, "{}", media)?;
+        }
+
+        Ok(())
+    }
+}
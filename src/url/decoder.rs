@@ -40,6 +40,7 @@ fn hex2byte(b: &[u8]) -> Option<u8> {
 /// ```
 pub struct UrlDecoder<'a> {
     inner: &'a str,
+    is_userinfo: bool,
 }
 
 
@@ -49,6 +50,17 @@ impl<'a> UrlDecoder<'a> {
     pub fn new(s: &'a str) -> UrlDecoder<'a> {
         UrlDecoder {
             inner: s,
+            is_userinfo: false,
+        }
+    }
+
+    /// Allocate UrlDecoder for username and password.
+    /// `+` symbol is not decoded as space
+    #[inline]
+    pub fn new_userinfo(s: &'a str) -> UrlDecoder<'a> {
+        UrlDecoder {
+            inner: s,
+            is_userinfo: true,
         }
     }
 
@@ -98,7 +110,7 @@ impl<'a> UrlDecoder<'a> {
                 } else {
                     return Err(fmt::Error);
                 }
-            } else if b == b'+' {
+            } else if b == b'+' && ! self.is_userinfo {
                 out.push(' ');
             } else {
                 out.push(char::from(b));
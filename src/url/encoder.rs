@@ -13,28 +13,24 @@ use std::fmt::{
 
 #[inline]
 fn is_rfc3986(b: u8) -> bool {
-    match b {
-        b'a' ..= b'z' => true,
-        b'A' ..= b'Z' => true,
-        b'0' ..= b'9' => true,
-        b'-' | b'_' | b'.' | b'~' => true,
-        b':' | b'@' => true,
-        _ => false,
-    }
+    matches!(b,
+        b'a' ..= b'z' |
+        b'A' ..= b'Z' |
+        b'0' ..= b'9' |
+        b'-' | b'_' | b'.' | b'~' |
+        b':' | b'@')
 }
 
 
 #[inline]
 fn is_rfc3986_path(b: u8) -> bool {
-    match b {
-        b'a' ..= b'z' => true,
-        b'A' ..= b'Z' => true,
-        b'0' ..= b'9' => true,
-        b'-' | b'_' | b'.' | b'~' => true,
-        b'/' | b',' | b'=' => true,
-        b':' | b'@' => true,
-        _ => false,
-    }
+    matches!(b,
+        b'a' ..= b'z' |
+        b'A' ..= b'Z' |
+        b'0' ..= b'9' |
+        b'-' | b'_' | b'.' | b'~' |
+        b'/' | b',' | b'=' |
+        b':' | b'@')
 }
 
 
@@ -38,12 +38,15 @@ type Result<T> = std::result::Result<T, UrlError>;
 /// A parsed URL record
 ///
 /// URL parts: `scheme://prefix@address/path?query#fragment`
+/// Prefix is a `username:password` pair.
 /// All url parts are optional.
 /// If path, query, and fragment are defined, then value contains their delimiter as well
 #[derive(Default, Debug, PartialEq, Clone)]
 pub struct Url {
     scheme: String,
     prefix: String,
+    username: String,
+    password: String,
     host: String,
     port: u16,
     path: String,
@@ -112,10 +115,18 @@ impl Url {
     #[inline]
     pub fn get_scheme(&self) -> &str { &self.scheme }
 
-    /// Returns URL prefix
+    /// Returns URL prefix as is, without decoding
     #[inline]
     pub fn get_prefix(&self) -> &str { &self.prefix }
 
+    /// Returns decoded username from the URL prefix
+    #[inline]
+    pub fn get_username(&self) -> &str { &self.username }
+
+    /// Returns decoded password from the URL prefix
+    #[inline]
+    pub fn get_password(&self) -> &str { &self.password }
+
     /// Returns URL host
     #[inline]
     pub fn get_host(&self) -> &str { &self.host }
@@ -186,6 +197,8 @@ impl UrlSetter for &str {
         if let Some(v) = self.find("://") {
             url.scheme.clear();
             url.prefix.clear();
+            url.username.clear();
+            url.password.clear();
             url.host.clear();
             url.port = 0;
             url.path.clear();
@@ -256,6 +269,11 @@ impl UrlSetter for &str {
 
         if let Some(prefix) = prefix {
             url.prefix.push_str(&self[skip .. prefix]);
+            let mut userinfo = url.prefix.splitn(2, ':');
+            UrlDecoder::new_userinfo(userinfo.next().unwrap()).decode(&mut url.username)?;
+            if let Some(password) = userinfo.next() {
+                UrlDecoder::new_userinfo(password).decode(&mut url.password)?;
+            }
             skip = prefix + 1;
         }
 
@@ -0,0 +1,109 @@
+use std::io::Write;
+
+use http::{
+    HttpClient,
+    HttpCookieJar,
+    Url,
+};
+
+mod support;
+use support::Server;
+
+
+#[test]
+fn test_cookie_login() {
+    Server::new("127.0.0.1:35300")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.url.get_path(), "/login");
+                assert_eq!(request.header.get("cookie"), None);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: /index.html\r\n",
+                    "Set-Cookie: sid=abc; Path=/; HttpOnly\r\n",
+                    "Set-Cookie: theme=dark; Path=/; Max-Age=3600\r\n",
+                    "Set-Cookie: tmp=1; Path=/login\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                assert_eq!(request.url.get_path(), "/index.html");
+                assert_eq!(request.header.get("cookie"), Some("sid=abc; theme=dark"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let jar = HttpCookieJar::new();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35300/login").unwrap();
+    client.set_cookie_jar(&jar);
+    client.get().unwrap();
+    assert_eq!(jar.len(), 3);
+
+    // persistence
+    let path = std::env::temp_dir().join(format!("libhttp-cookie-{}.txt", std::process::id()));
+    jar.save(&path).unwrap();
+
+    let jar = HttpCookieJar::new();
+    jar.load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let url = Url::new("http://127.0.0.1:35300/").unwrap();
+    assert_eq!(jar.get_cookie(&url).as_deref(), Some("sid=abc; theme=dark"));
+}
+
+
+#[test]
+fn test_cookie_merge() {
+    Server::new("127.0.0.1:35301")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.header.get("cookie"), Some("lang=en; sid=user"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Set-Cookie: theme=dark\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                assert_eq!(request.header.get("cookie"), Some("lang=en; sid=user; theme=dark"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let jar = HttpCookieJar::new();
+    let url = Url::new("http://127.0.0.1:35301/").unwrap();
+    jar.set_cookie(&url, "sid=jar");
+
+    let mut client = HttpClient::new("http://127.0.0.1:35301/").unwrap();
+    client.set_cookie_jar(&jar);
+    client.request.header.set("Cookie", "lang=en; sid=user");
+    client.get().unwrap();
+
+    // cookies from the jar are not duplicated in the next request
+    client.get().unwrap();
+}
@@ -0,0 +1,144 @@
+use std::{
+    io::{
+        BufReader,
+        Cursor,
+        Seek,
+        SeekFrom,
+        Write,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+    thread,
+};
+
+use http::{
+    HttpClient,
+    HttpClientError,
+};
+
+
+type FnHandler = fn(&http::Request, &mut TcpStream);
+
+
+/// Handles each connection with next handler and closes it
+fn serve(addr: &str, handlers: Vec<FnHandler>) {
+    let listener = TcpListener::bind(addr).unwrap();
+
+    thread::spawn(move || {
+        for handler in handlers {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = http::Request::default();
+            request.parse(&mut BufReader::new(&mut stream)).unwrap();
+            handler(&request, &mut stream);
+        }
+    });
+}
+
+
+#[test]
+fn test_download_resume() {
+    serve("127.0.0.1:35500", vec![
+        |request, stream| {
+            assert_eq!(request.header.get("range"), None);
+            assert_eq!(request.header.get("accept-encoding"), Some("identity"));
+            stream.write_all(concat!(
+                "HTTP/1.1 200 Ok\r\n",
+                "ETag: \"v1\"\r\n",
+                "Content-Length: 26\r\n",
+                "\r\n",
+                "abcdefghij"
+            ).as_bytes()).unwrap();
+        },
+        |request, stream| {
+            assert_eq!(request.header.get("range"), Some("bytes=10-"));
+            assert_eq!(request.header.get("if-range"), Some("\"v1\""));
+            stream.write_all(concat!(
+                "HTTP/1.1 206 Partial Content\r\n",
+                "ETag: \"v1\"\r\n",
+                "Content-Range: bytes 10-25/26\r\n",
+                "Content-Length: 16\r\n",
+                "\r\n",
+                "klmnopqrstuvwxyz"
+            ).as_bytes()).unwrap();
+        },
+    ]);
+
+    let mut dst = Cursor::new(Vec::new());
+    let mut client = HttpClient::new("http://127.0.0.1:35500/record.ts").unwrap();
+    assert_eq!(client.download(&mut dst).unwrap(), 26);
+    assert_eq!(dst.get_ref().as_slice(), b"abcdefghijklmnopqrstuvwxyz");
+    assert_eq!(client.request.header.get("range"), None);
+}
+
+
+#[test]
+fn test_download_fallback() {
+    serve("127.0.0.1:35501", vec![
+        |request, stream| {
+            assert_eq!(request.header.get("range"), Some("bytes=5-"));
+            assert_eq!(request.header.get("if-range"), None);
+            stream.write_all(concat!(
+                "HTTP/1.1 200 Ok\r\n",
+                "Content-Length: 13\r\n",
+                "\r\n",
+                "Hello, world!"
+            ).as_bytes()).unwrap();
+        },
+    ]);
+
+    let mut dst = Cursor::new(b"xxxxx".to_vec());
+    dst.seek(SeekFrom::End(0)).unwrap();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35501/record.ts").unwrap();
+    assert_eq!(client.download(&mut dst).unwrap(), 13);
+    assert_eq!(dst.get_ref().as_slice(), b"Hello, world!");
+}
+
+
+#[test]
+fn test_download_invalid_range() {
+    serve("127.0.0.1:35502", vec![
+        |_request, stream| {
+            stream.write_all(concat!(
+                "HTTP/1.1 206 Partial Content\r\n",
+                "Content-Range: bytes 0-12/13\r\n",
+                "Content-Length: 13\r\n",
+                "\r\n",
+                "Hello, world!"
+            ).as_bytes()).unwrap();
+        },
+    ]);
+
+    let mut dst = Cursor::new(b"Hello".to_vec());
+    dst.seek(SeekFrom::End(0)).unwrap();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35502/record.ts").unwrap();
+    match client.download(&mut dst) {
+        Err(HttpClientError::InvalidContentRange) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+}
+
+
+#[test]
+fn test_download_complete() {
+    serve("127.0.0.1:35503", vec![
+        |request, stream| {
+            assert_eq!(request.header.get("range"), Some("bytes=13-"));
+            stream.write_all(concat!(
+                "HTTP/1.1 416 Range Not Satisfiable\r\n",
+                "Content-Range: bytes */13\r\n",
+                "Content-Length: 0\r\n",
+                "\r\n"
+            ).as_bytes()).unwrap();
+        },
+    ]);
+
+    let mut dst = Cursor::new(b"Hello, world!".to_vec());
+    dst.seek(SeekFrom::End(0)).unwrap();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35503/record.ts").unwrap();
+    assert_eq!(client.download(&mut dst).unwrap(), 13);
+}
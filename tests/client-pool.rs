@@ -0,0 +1,163 @@
+use std::{
+    time::Duration,
+    io::{
+        Cursor,
+        Read,
+        Write,
+    },
+};
+
+use http::{
+    HttpClient,
+    HttpClientError,
+    HttpPool,
+};
+
+mod support;
+use support::Server;
+
+
+fn response_peer_port(writer: &mut std::io::BufWriter<std::net::TcpStream>) -> std::io::Result<()> {
+    let peer_port = writer.get_ref().peer_addr().unwrap().port().to_string();
+    writer.write_all(format!(concat!(
+        "HTTP/1.1 200 Ok\r\n",
+        "Connection: keep-alive\r\n",
+        "Content-Length: {}\r\n",
+        "\r\n",
+        "{}"
+    ), peer_port.len(), peer_port).as_bytes())
+}
+
+
+fn get_peer_port(pool: &HttpPool, url: &str) -> String {
+    let mut client = HttpClient::new(url).unwrap();
+    client.set_pool(pool);
+    client.get().unwrap();
+
+    let mut body = String::new();
+    client.read_to_string(&mut body).unwrap();
+    body
+}
+
+
+#[test]
+fn test_pool_reuse() {
+    Server::new("127.0.0.1:35000")
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .run();
+
+    let pool = HttpPool::new();
+
+    let port1 = get_peer_port(&pool, "http://127.0.0.1:35000/");
+    assert_eq!(pool.get_idle(), 1);
+
+    let port2 = get_peer_port(&pool, "http://127.0.0.1:35000/");
+    assert_eq!(pool.get_idle(), 1);
+
+    assert_eq!(port1, port2);
+}
+
+
+#[test]
+fn test_pool_idle_timeout() {
+    Server::new("127.0.0.1:35001")
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .run();
+
+    let pool = HttpPool::new();
+    pool.set_idle_timeout(Duration::from_millis(50));
+
+    let port1 = get_peer_port(&pool, "http://127.0.0.1:35001/");
+    assert_eq!(pool.get_idle(), 1);
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(pool.get_idle(), 0);
+
+    let port2 = get_peer_port(&pool, "http://127.0.0.1:35001/");
+    assert_ne!(port1, port2);
+}
+
+
+#[test]
+fn test_pool_limits() {
+    Server::new("127.0.0.1:35002")
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .run();
+
+    Server::new("127.0.0.1:35003")
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .run();
+
+    let pool = HttpPool::new();
+    pool.set_max_idle(1);
+
+    get_peer_port(&pool, "http://127.0.0.1:35002/");
+    assert_eq!(pool.get_idle(), 1);
+
+    get_peer_port(&pool, "http://127.0.0.1:35003/");
+    assert_eq!(pool.get_idle(), 1);
+
+    pool.set_max_idle_per_host(0);
+    assert_eq!(pool.get_idle(), 0);
+}
+
+
+#[test]
+fn test_pool_release_buffered() {
+    Server::new("127.0.0.1:35004")
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .run();
+
+    let pool = HttpPool::new();
+
+    // download() reads content with fill_buf() and consume()
+    let mut client = HttpClient::new("http://127.0.0.1:35004/").unwrap();
+    client.set_pool(&pool);
+    let mut body = Cursor::new(Vec::new());
+    client.download(&mut body).unwrap();
+    assert_eq!(pool.get_idle(), 1);
+}
+
+
+#[test]
+fn test_pool_max_connections() {
+    Server::new("127.0.0.1:35005")
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .run();
+
+    Server::new("127.0.0.1:35006")
+        .step(|_request, _reader| Ok(()), response_peer_port)
+        .run();
+
+    let pool = HttpPool::new();
+    pool.set_max_connections(1);
+
+    // content is not readed, connection is active
+    let mut client1 = HttpClient::new("http://127.0.0.1:35005/").unwrap();
+    client1.set_pool(&pool);
+    client1.get().unwrap();
+    assert_eq!(pool.get_connections(), 1);
+
+    let mut client2 = HttpClient::new("http://127.0.0.1:35006/").unwrap();
+    client2.set_pool(&pool);
+    client2.set_connect_timeout(Duration::from_millis(100));
+    match client2.get() {
+        Err(HttpClientError::Timeout) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+
+    // idle connection closes for the new one
+    let mut body = String::new();
+    client1.read_to_string(&mut body).unwrap();
+    assert_eq!(pool.get_idle(), 1);
+
+    client2.get().unwrap();
+    assert_eq!(pool.get_idle(), 0);
+    assert_eq!(pool.get_connections(), 1);
+
+    drop(client1);
+    drop(client2);
+    assert_eq!(pool.get_connections(), 0);
+}
@@ -0,0 +1,52 @@
+use std::io::{
+    Read,
+    Write,
+};
+
+use http::HttpClient;
+
+mod support;
+use support::{
+    Server,
+    HELLO_WORLD,
+};
+
+
+// Environment is shared between tests in the same process.
+// This file should contain only one test
+#[test]
+fn test_proxy_env() {
+    Server::new("127.0.0.1:35106")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.url.get_host(), "example.test");
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 13\r\n",
+                    "\r\n",
+                    "Hello, world!"
+                ).as_bytes())
+            })
+        .run();
+
+    std::env::set_var("http_proxy", "127.0.0.1:35106");
+    std::env::set_var("no_proxy", "localhost,.example.local");
+
+    let mut client = HttpClient::new("http://example.test/path").unwrap();
+    client.get().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+
+    // direct connection to the host from no_proxy
+    std::env::set_var("no_proxy", "example.test");
+    let mut client = HttpClient::new("http://example.test/path").unwrap();
+    assert!(client.get().is_err());
+
+    std::env::remove_var("http_proxy");
+    std::env::remove_var("no_proxy");
+}
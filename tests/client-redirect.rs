@@ -0,0 +1,397 @@
+use std::io::{
+    Read,
+    Write,
+};
+
+use http::{
+    HttpClient,
+    HttpClientError,
+    HttpCookieJar,
+};
+
+mod support;
+use support::{
+    Server,
+    HELLO_WORLD,
+};
+
+
+fn read_body(request: &http::Request, reader: &mut std::io::BufReader<std::net::TcpStream>) -> Vec<u8> {
+    let len: usize = request.header.get("content-length").unwrap().parse().unwrap();
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).unwrap();
+    body
+}
+
+
+#[test]
+fn test_redirect_307() {
+    Server::new("127.0.0.1:35200")
+        .step(
+            |request, reader| {
+                assert_eq!(request.get_method(), "POST");
+                assert_eq!(request.url.get_path(), "/upload");
+                assert_eq!(read_body(request, reader).as_slice(), HELLO_WORLD);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 307 Temporary Redirect\r\n",
+                    "Location: /node-2/upload\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, reader| {
+                assert_eq!(request.get_method(), "POST");
+                assert_eq!(request.url.get_path(), "/node-2/upload");
+                assert_eq!(read_body(request, reader).as_slice(), HELLO_WORLD);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35200/upload").unwrap();
+    client.request.set_method("POST");
+    client.set_body(HELLO_WORLD);
+    client.get().unwrap();
+
+    assert_eq!(client.get_redirects(), &[
+        "http://127.0.0.1:35200/upload",
+        "http://127.0.0.1:35200/node-2/upload",
+    ]);
+}
+
+
+#[test]
+fn test_redirect_303() {
+    Server::new("127.0.0.1:35201")
+        .step(
+            |request, reader| {
+                assert_eq!(request.get_method(), "POST");
+                assert_eq!(read_body(request, reader).as_slice(), HELLO_WORLD);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 303 See Other\r\n",
+                    "Location: /result\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                assert_eq!(request.get_method(), "GET");
+                assert_eq!(request.url.get_path(), "/result");
+                assert_eq!(request.header.get("content-length"), None);
+                assert_eq!(request.header.get("content-type"), None);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 13\r\n",
+                    "\r\n",
+                    "Hello, world!"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35201/form").unwrap();
+    client.request.set_method("POST");
+    client.request.header.set("Content-Type", "text/plain");
+    client.set_body(HELLO_WORLD);
+    client.get().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
+
+
+#[test]
+fn test_redirect_loop() {
+    Server::new("127.0.0.1:35202")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: /b\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                assert_eq!(request.url.get_path(), "/b");
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: /a\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35202/a").unwrap();
+    match client.get() {
+        Err(HttpClientError::RedirectLoop) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+}
+
+
+#[test]
+fn test_redirect_max() {
+    Server::new("127.0.0.1:35203")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 308 Permanent Redirect\r\n",
+                    "Location: /b\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 308 Permanent Redirect\r\n",
+                    "Location: /c\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35203/a").unwrap();
+    client.set_max_redirects(1);
+    match client.get() {
+        Err(HttpClientError::TooManyRedirects) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+}
+
+
+#[test]
+fn test_redirect_cross_origin() {
+    Server::new("127.0.0.1:35204")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.header.get("authorization"), Some("Bearer token"));
+                assert_eq!(request.header.get("cookie"), Some("session=1"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: http://127.0.0.1:35205/ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    Server::new("127.0.0.1:35205")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.header.get("host"), Some("127.0.0.1:35205"));
+                assert_eq!(request.header.get("authorization"), None);
+                assert_eq!(request.header.get("cookie"), None);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35204/").unwrap();
+    client.request.header.set("Authorization", "Bearer token");
+    client.request.header.set("Cookie", "session=1");
+    client.get().unwrap();
+    assert_eq!(client.response.get_code(), 200);
+}
+
+
+#[test]
+fn test_redirect_login() {
+    Server::new("127.0.0.1:35206")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.url.get_path(), "/a");
+                assert_eq!(request.header.get("cookie"), None);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: /login\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                assert_eq!(request.url.get_path(), "/login");
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: /a\r\n",
+                    "Set-Cookie: sid=1; Path=/\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                // same url with session cookie is not a loop
+                assert_eq!(request.url.get_path(), "/a");
+                assert_eq!(request.header.get("cookie"), Some("sid=1"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35206/a").unwrap();
+    client.set_cookie_jar(&HttpCookieJar::new());
+    client.get().unwrap();
+    assert_eq!(client.response.get_code(), 200);
+    assert_eq!(client.get_redirects(), &[
+        "http://127.0.0.1:35206/a",
+        "http://127.0.0.1:35206/login",
+        "http://127.0.0.1:35206/a",
+    ]);
+}
+
+
+#[test]
+fn test_redirect_cross_origin_return() {
+    Server::new("127.0.0.1:35207")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.header.get("authorization"), Some("Bearer token"));
+                assert_eq!(request.header.get("cookie"), Some("session=1"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: http://127.0.0.1:35208/x\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                // headers restored on the first origin
+                assert_eq!(request.url.get_path(), "/b");
+                assert_eq!(request.header.get("authorization"), Some("Bearer token"));
+                assert_eq!(request.header.get("cookie"), Some("session=1"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    Server::new("127.0.0.1:35208")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.header.get("authorization"), None);
+                assert_eq!(request.header.get("cookie"), None);
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: http://127.0.0.1:35207/b\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35207/a").unwrap();
+    client.request.header.set("Authorization", "Bearer token");
+    client.request.header.set("Cookie", "session=1");
+    client.get().unwrap();
+    assert_eq!(client.response.get_code(), 200);
+}
+
+
+#[test]
+fn test_redirect_manual() {
+    fn redirect(location: &'static str) -> std::io::Result<Vec<u8>> {
+        Ok(format!(concat!(
+            "HTTP/1.1 302 Found\r\n",
+            "Location: {}\r\n",
+            "Content-Length: 0\r\n",
+            "\r\n"
+        ), location).into_bytes())
+    }
+
+    let mut server = Server::new("127.0.0.1:35209");
+    for _ in 0 .. 2 {
+        server = server
+            .step(|_request, _reader| Ok(()), |writer| writer.write_all(&redirect("/b")?))
+            .step(|_request, _reader| Ok(()), |writer| writer.write_all(&redirect("/c")?))
+            .step(
+                |request, _reader| {
+                    assert_eq!(request.url.get_path(), "/c");
+                    Ok(())
+                },
+                |writer| {
+                    writer.write_all(concat!(
+                        "HTTP/1.1 200 Ok\r\n",
+                        "Content-Length: 0\r\n",
+                        "\r\n"
+                    ).as_bytes())
+                });
+    }
+    server.run();
+
+    // unrelated requests with the same redirect chain
+    let mut client = HttpClient::new("http://127.0.0.1:35209/a").unwrap();
+    for _ in 0 .. 2 {
+        client.request.url.set("http://127.0.0.1:35209/a").unwrap();
+        client.send().unwrap();
+        client.receive().unwrap();
+        while client.response.get_code() == 302 {
+            client.redirect().unwrap();
+            client.send().unwrap();
+            client.receive().unwrap();
+        }
+        assert_eq!(client.response.get_code(), 200);
+        assert_eq!(client.get_redirects().len(), 3);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    io::{
+        BufReader,
+        Read,
+        Write,
+    },
+    net::TcpListener,
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use http::{
+    HttpClient,
+    HttpClientError,
+};
+
+mod support;
+use support::Server;
+
+
+#[test]
+fn test_retry_stale_connection() {
+    let listener = TcpListener::bind("127.0.0.1:35400").unwrap();
+
+    thread::spawn(move || {
+        for _ in 0 .. 2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = http::Request::default();
+            request.parse(&mut BufReader::new(&mut stream)).unwrap();
+            stream.write_all(concat!(
+                "HTTP/1.1 200 Ok\r\n",
+                "Content-Length: 13\r\n",
+                "\r\n",
+                "Hello, world!"
+            ).as_bytes()).unwrap();
+            // close kept-alive connection
+        }
+    });
+
+    let mut client = HttpClient::new("http://127.0.0.1:35400/").unwrap();
+
+    for _ in 0 .. 2 {
+        client.get().unwrap();
+        let mut body = String::new();
+        client.read_to_string(&mut body).unwrap();
+        assert_eq!(body, "Hello, world!");
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+
+#[test]
+fn test_retry_stale_connection_send() {
+    let listener = TcpListener::bind("127.0.0.1:35404").unwrap();
+
+    thread::spawn(move || {
+        for _ in 0 .. 2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = http::Request::default();
+            request.parse(&mut BufReader::new(&mut stream)).unwrap();
+            stream.write_all(concat!(
+                "HTTP/1.1 200 Ok\r\n",
+                "Content-Length: 13\r\n",
+                "\r\n",
+                "Hello, world!"
+            ).as_bytes()).unwrap();
+            // close kept-alive connection
+        }
+    });
+
+    let mut client = HttpClient::new("http://127.0.0.1:35404/").unwrap();
+
+    for _ in 0 .. 2 {
+        client.send().unwrap();
+        client.receive().unwrap();
+        assert_eq!(200, client.response.get_code());
+        let mut body = String::new();
+        client.read_to_string(&mut body).unwrap();
+        assert_eq!(body, "Hello, world!");
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+
+#[test]
+fn test_retry_after() {
+    Server::new("127.0.0.1:35401")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 503 Service Unavailable\r\n",
+                    "Retry-After: 1\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 502 Bad Gateway\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35401/").unwrap();
+    client.set_max_retries(3);
+    client.set_retry_delay(Duration::from_millis(10), Duration::from_secs(2));
+
+    let now = Instant::now();
+    client.get().unwrap();
+    assert_eq!(client.response.get_code(), 200);
+    assert!(now.elapsed() >= Duration::from_secs(1));
+}
+
+
+#[test]
+fn test_retry_not_allowed() {
+    Server::new("127.0.0.1:35402")
+        .step(
+            |request, reader| {
+                assert_eq!(request.get_method(), "POST");
+                let mut body = [0; 13];
+                reader.read_exact(&mut body)?;
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 503 Service Unavailable\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |request, _reader| {
+                assert_eq!(request.get_method(), "GET");
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 503 Service Unavailable\r\n",
+                    "Retry-After: 120\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35402/").unwrap();
+    client.set_max_retries(3);
+
+    // POST is not idempotent
+    client.request.set_method("POST");
+    client.set_body("Hello, world!");
+    match client.get() {
+        Err(HttpClientError::RequestFailed(503, _)) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+
+    // Retry-After exceeds maximum delay
+    client.request.set_method("GET");
+    client.set_body("");
+    client.request.header.remove("content-length");
+    match client.get() {
+        Err(HttpClientError::RequestFailed(503, _)) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+}
+
+
+#[test]
+fn test_retry_connect() {
+    let mut client = HttpClient::new("http://127.0.0.1:35403/").unwrap();
+    client.set_max_retries(2);
+    client.set_retry_delay(Duration::from_millis(100), Duration::from_secs(1));
+
+    // server starts after first attempt
+    thread::spawn(|| {
+        thread::sleep(Duration::from_millis(30));
+        Server::new("127.0.0.1:35403")
+            .step(
+                |_request, _reader| Ok(()),
+                |writer| {
+                    writer.write_all(concat!(
+                        "HTTP/1.1 200 Ok\r\n",
+                        "Content-Length: 0\r\n",
+                        "\r\n"
+                    ).as_bytes())
+                })
+            .run();
+    });
+
+    client.get().unwrap();
+    assert_eq!(client.response.get_code(), 200);
+}
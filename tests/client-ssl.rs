@@ -1,9 +1,24 @@
 use std::io::Read;
 
-use http::HttpClient;
+use openssl::ssl::SslVersion;
+
+use http::{
+    HttpClient,
+    HttpTls,
+    HttpTlsConfig,
+    HttpTlsVersion,
+};
 
 mod support;
-use support::HELLO_WORLD;
+use support::{
+    HELLO_WORLD,
+    tls::{
+        cert_dir,
+        spki_pin,
+        tls_server,
+        TlsServerConfig,
+    },
+};
 
 
 #[test]
@@ -26,3 +41,181 @@ fn test_get_expired_ssl() {
         Err(ref e) => println!("test_get_expired_ssl(): {}", e),
     }
 }
+
+
+fn get_hello(client: &mut HttpClient) {
+    client.get().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
+
+
+#[test]
+fn test_tls_untrusted() {
+    tls_server("127.0.0.1:36000", TlsServerConfig::default());
+
+    let mut client = HttpClient::new("https://localhost:36000/").unwrap();
+    assert!(client.get().is_err());
+}
+
+
+#[test]
+fn test_tls_insecure() {
+    tls_server("127.0.0.1:36001", TlsServerConfig::default());
+
+    let mut tls = HttpTlsConfig::new();
+    tls.set_insecure(true);
+
+    // certificate is not trusted
+    let mut client = HttpClient::new("https://localhost:36001/").unwrap();
+    client.set_tls(&tls).unwrap();
+    get_hello(&mut client);
+}
+
+
+#[test]
+fn test_tls_ca_file() {
+    tls_server("127.0.0.1:36002", TlsServerConfig::default());
+
+    let mut tls = HttpTlsConfig::new();
+    tls.set_no_default_ca(true);
+    tls.add_ca_file(cert_dir().join("ca.pem"));
+
+    let mut client = HttpClient::new("https://localhost:36002/").unwrap();
+    client.set_tls(&tls).unwrap();
+    get_hello(&mut client);
+}
+
+
+#[test]
+fn test_tls_client_certificate() {
+    tls_server("127.0.0.1:36003", TlsServerConfig {
+        client_cert: true,
+        connections: 2,
+        .. TlsServerConfig::default()
+    });
+
+    let dir = cert_dir();
+
+    let mut tls = HttpTlsConfig::new();
+    tls.add_ca_file(dir.join("ca.pem"));
+
+    // without certificate
+    let mut client = HttpClient::new("https://localhost:36003/").unwrap();
+    client.set_tls(&tls).unwrap();
+    assert!(client.get().is_err());
+
+    tls.set_certificate(dir.join("client.pem"), dir.join("client.key"));
+    let mut client = HttpClient::new("https://localhost:36003/").unwrap();
+    client.set_tls(&tls).unwrap();
+    get_hello(&mut client);
+}
+
+
+#[test]
+fn test_tls_min_version() {
+    tls_server("127.0.0.1:36004", TlsServerConfig {
+        max_version: Some(SslVersion::TLS1_2),
+        .. TlsServerConfig::default()
+    });
+
+    let mut tls = HttpTlsConfig::new();
+    tls.add_ca_file(cert_dir().join("ca.pem"));
+    tls.set_min_version(HttpTlsVersion::TLS13);
+
+    let mut client = HttpClient::new("https://localhost:36004/").unwrap();
+    client.set_tls(&tls).unwrap();
+    assert!(client.get().is_err());
+}
+
+
+#[test]
+fn test_tls_invalid_config() {
+    let mut tls = HttpTlsConfig::new();
+    tls.add_ca_file("/nonexistent/ca.pem");
+
+    let mut client = HttpClient::new("https://localhost/").unwrap();
+    assert!(client.set_tls(&tls).is_err());
+}
+
+
+#[test]
+fn test_tls_pin() {
+    tls_server("127.0.0.1:36005", TlsServerConfig {
+        connections: 2,
+        .. TlsServerConfig::default()
+    });
+
+    let mut tls = HttpTlsConfig::new();
+    tls.add_ca_file(cert_dir().join("ca.pem"));
+    tls.add_pin("localhost", "sha256//AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+
+    let mut client = HttpClient::new("https://localhost:36005/").unwrap();
+    client.set_tls(&tls).unwrap();
+    let e = client.get().unwrap_err();
+    assert!(e.to_string().contains("pin mismatch"));
+
+    // backup pin
+    tls.add_pin("localhost", &spki_pin("server.pem"));
+    let mut client = HttpClient::new("https://localhost:36005/").unwrap();
+    client.set_tls(&tls).unwrap();
+    get_hello(&mut client);
+}
+
+
+#[test]
+fn test_tls_session_reuse() {
+    tls_server("127.0.0.1:36006", TlsServerConfig {
+        connections: 2,
+        .. TlsServerConfig::default()
+    });
+
+    let mut tls = HttpTlsConfig::new();
+    tls.add_ca_file(cert_dir().join("ca.pem"));
+
+    let mut client = HttpClient::new("https://localhost:36006/").unwrap();
+    client.set_tls(&tls).unwrap();
+
+    client.get().unwrap();
+    assert!(! client.is_session_reused());
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+
+    // reconnect after Connection: close
+    client.get().unwrap();
+    assert!(client.is_session_reused());
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
+
+
+#[test]
+fn test_tls_shared_context() {
+    tls_server("127.0.0.1:36007", TlsServerConfig {
+        connections: 2,
+        .. TlsServerConfig::default()
+    });
+
+    let mut config = HttpTlsConfig::new();
+    config.add_ca_file(cert_dir().join("ca.pem"));
+    let tls = HttpTls::new(&config).unwrap();
+
+    let mut client = HttpClient::new("https://localhost:36007/").unwrap();
+    client.set_tls_context(&tls);
+    client.get().unwrap();
+    assert!(! client.is_session_reused());
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+
+    // session from the first client
+    let mut client = HttpClient::new("https://localhost:36007/").unwrap();
+    client.set_tls_context(&tls);
+    client.get().unwrap();
+    assert!(client.is_session_reused());
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
@@ -7,7 +7,11 @@ use std::{
     },
 };
 
-use http::HttpClient;
+use http::{
+    Header,
+    HttpClient,
+    HttpClientError,
+};
 
 mod support;
 use support::{
@@ -136,7 +140,75 @@ fn test_post() {
     client.request.header.set("content-type", "text/plain");
     client.request.header.set("content-length", HELLO_WORLD.len());
     client.send().unwrap();
-    client.write(HELLO_WORLD).unwrap();
+    client.write_all(HELLO_WORLD).unwrap();
+    client.receive().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
+
+
+#[test]
+fn test_post_chunked() {
+    Server::new("127.0.0.1:33019")
+        .step(
+            |request, reader| {
+                assert_eq!(request.header.get("transfer-encoding"), Some("chunked"));
+
+                let mut body = Vec::new();
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line)?;
+                    let len = usize::from_str_radix(line.trim(), 16).unwrap();
+                    if len == 0 {
+                        break;
+                    }
+                    let skip = body.len();
+                    body.resize(skip + len + 2, 0);
+                    reader.read_exact(&mut body[skip ..])?;
+                    assert_eq!(&body[skip + len ..], b"\r\n");
+                    body.truncate(skip + len);
+                }
+
+                let mut trailer = Header::default();
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line)?;
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    trailer.parse(line.trim());
+                }
+
+                assert_eq!(body.len(), 10 * HELLO_WORLD.len() + 20000);
+                assert_eq!(&body[.. HELLO_WORLD.len()], HELLO_WORLD);
+                assert_eq!(trailer.get("x-checksum"), Some("test"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 13\r\n",
+                    "\r\n",
+                    "Hello, world!"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:33019").unwrap();
+    client.request.set_method("POST");
+    client.request.header.set("transfer-encoding", "chunked");
+    client.send().unwrap();
+    for _ in 0 .. 10 {
+        client.write_all(HELLO_WORLD).unwrap();
+    }
+    client.write_all(&[b'x'; 20000]).unwrap();
+
+    let mut trailer = Header::default();
+    trailer.set("X-Checksum", "test");
+    client.finish_trailer(&trailer).unwrap();
     client.receive().unwrap();
 
     let mut body = Vec::with_capacity(64);
@@ -275,6 +347,96 @@ fn test_get_timeout() {
 }
 
 
+#[test]
+fn test_read_timeout() {
+    Server::new("127.0.0.1:33017")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                thread::sleep(std::time::Duration::from_millis(500));
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "\r\n",
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:33017").unwrap();
+    client.set_read_timeout(std::time::Duration::from_millis(100));
+    match client.get() {
+        Err(HttpClientError::Timeout) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+}
+
+
+#[test]
+fn test_total_timeout() {
+    Server::new("127.0.0.1:33018")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                thread::sleep(std::time::Duration::from_millis(300));
+                writer.write_all(concat!(
+                    "HTTP/1.1 302 Found\r\n",
+                    "Location: /ok/\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                thread::sleep(std::time::Duration::from_millis(300));
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:33018/redirect/").unwrap();
+    client.set_read_timeout(std::time::Duration::from_secs(1));
+    client.set_timeout(std::time::Duration::from_millis(400));
+
+    let now = std::time::Instant::now();
+    match client.get() {
+        Err(HttpClientError::Timeout) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+    assert!(now.elapsed() < std::time::Duration::from_millis(550));
+}
+
+
+#[test]
+fn test_total_timeout_content() {
+    Server::new("127.0.0.1:33021")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 13\r\n",
+                    "\r\n"
+                ).as_bytes())?;
+                writer.flush()?;
+                thread::sleep(std::time::Duration::from_millis(300));
+                writer.write_all(HELLO_WORLD)
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:33021").unwrap();
+    client.set_read_timeout(std::time::Duration::from_secs(1));
+    client.set_timeout(std::time::Duration::from_millis(100));
+    client.get().unwrap();
+
+    // overall timeout covers request and headers only
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
+
+
 #[test]
 fn test_404_without_body() {
     Server::new("127.0.0.1:33011")
@@ -438,3 +600,115 @@ fn test_redirect_absolute_url() {
     client.get().unwrap();
     assert_eq!(200, client.response.get_code());
 }
+
+
+#[test]
+fn test_content_encoding_gzip() {
+    Server::new("127.0.0.1:33015")
+        .step(
+            |request, _reader| {
+                assert_eq!(request.header.get("accept-encoding"), Some("gzip, deflate"));
+                Ok(())
+            },
+            |writer| {
+                let mut encoder = libflate::gzip::Encoder::new(Vec::new())?;
+                encoder.write_all(HELLO_WORLD)?;
+                let body = encoder.finish().into_result()?;
+
+                writer.write_all(format!(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Encoding: gzip\r\n",
+                    "Content-Length: {}\r\n",
+                    "\r\n"
+                ), body.len()).as_bytes())?;
+                writer.write_all(&body)
+            })
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 13\r\n",
+                    "\r\n",
+                    "Hello, world!"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:33015").unwrap();
+    client.get().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+
+    // next request on the same connection
+    client.get().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
+
+
+#[test]
+fn test_content_encoding_deflate_chunked() {
+    Server::new("127.0.0.1:33016")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                let mut encoder = libflate::zlib::Encoder::new(Vec::new())?;
+                encoder.write_all(HELLO_WORLD)?;
+                let body = encoder.finish().into_result()?;
+
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Encoding: deflate\r\n",
+                    "Transfer-Encoding: chunked\r\n",
+                    "\r\n"
+                ).as_bytes())?;
+                for chunk in body.chunks(4) {
+                    writer.write_fmt(format_args!("{:x}\r\n", chunk.len()))?;
+                    writer.write_all(chunk)?;
+                    writer.write_all(b"\r\n")?;
+                }
+                writer.write_all(b"0\r\n\r\n")
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:33016").unwrap();
+    client.get().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
+
+
+#[test]
+fn test_content_encoding_deflate_raw() {
+    Server::new("127.0.0.1:33020")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                let mut encoder = libflate::deflate::Encoder::new(Vec::new());
+                encoder.write_all(HELLO_WORLD)?;
+                let body = encoder.finish().into_result()?;
+
+                writer.write_all(format!(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Encoding: deflate\r\n",
+                    "Content-Length: {}\r\n",
+                    "\r\n"
+                ), body.len()).as_bytes())?;
+                writer.write_all(&body)
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:33020").unwrap();
+    client.get().unwrap();
+
+    let mut body = Vec::with_capacity(64);
+    client.read_to_end(&mut body).unwrap();
+    assert_eq!(HELLO_WORLD, body.as_slice());
+}
@@ -9,3 +9,85 @@ fn test_key_cap() {
     h.send(&mut result).unwrap();
     assert_eq!(&result, b"X-Forwarded-For: test\r\n");
 }
+
+
+#[test]
+fn test_multiple_values() {
+    let mut h = Header::default();
+    h.parse("Set-Cookie: a=1");
+    h.parse("set-cookie: b=2");
+    assert_eq!(h.get("set-cookie"), Some("a=1"));
+    assert_eq!(h.get_all("Set-Cookie").collect::<Vec<&str>>(), vec!["a=1", "b=2"]);
+
+    let mut result = Vec::<u8>::new();
+    h.send(&mut result).unwrap();
+    assert_eq!(&result, b"Set-Cookie: a=1\r\nset-cookie: b=2\r\n");
+
+    h.set("Set-Cookie", "c=3");
+    assert_eq!(h.get_all("set-cookie").count(), 1);
+    assert_eq!(h.get_all("via").count(), 0);
+}
+
+
+#[test]
+fn test_name_case() {
+    let mut h = Header::default();
+    h.parse("content-TYPE: text/plain");
+    h.append("x-REQUEST-id", "1");
+    assert_eq!(h.get("Content-Type"), Some("text/plain"));
+    assert_eq!(h.iter().collect::<Vec<(&str, &str)>>(), vec![
+        ("content-TYPE", "text/plain"),
+        ("x-REQUEST-id", "1"),
+    ]);
+
+    // set keeps name of the new value
+    h.set("Content-Type", "text/html");
+    let mut result = Vec::<u8>::new();
+    h.send(&mut result).unwrap();
+    assert_eq!(&result, b"Content-Type: text/html\r\nx-REQUEST-id: 1\r\n");
+}
+
+
+#[test]
+fn test_order() {
+    let mut h = Header::default();
+    h.set("Host", "example.com");
+    h.set("User-Agent", "libhttp");
+    h.append("Via", "1.1 proxy-a");
+    h.set("Accept", "*/*");
+    h.append("via", "1.1 proxy-b");
+    h.set("HOST", "example.org");
+
+    let mut result = Vec::<u8>::new();
+    h.send(&mut result).unwrap();
+    assert_eq!(std::str::from_utf8(&result).unwrap(), concat!(
+        "HOST: example.org\r\n",
+        "User-Agent: libhttp\r\n",
+        "Via: 1.1 proxy-a\r\n",
+        "Accept: */*\r\n",
+        "via: 1.1 proxy-b\r\n"));
+
+    // set replaces all values and keeps position
+    h.set("Via", "1.1 proxy-c");
+    assert_eq!(h.iter().collect::<Vec<(&str, &str)>>(), vec![
+        ("HOST", "example.org"),
+        ("User-Agent", "libhttp"),
+        ("Via", "1.1 proxy-c"),
+        ("Accept", "*/*"),
+    ]);
+
+    h.remove("user-agent");
+    assert_eq!(h.get("User-Agent"), None);
+    assert_eq!(h.len(), 3);
+}
+
+
+#[test]
+fn test_similar_keys() {
+    let mut h = Header::default();
+    h.set("X-Key", "1");
+    h.set("X-Key-2", "2");
+    assert_eq!(h.get("x-key"), Some("1"));
+    assert_eq!(h.get("x-key-2"), Some("2"));
+    assert_eq!(h.get("x-ke"), None);
+}
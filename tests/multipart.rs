@@ -0,0 +1,249 @@
+use std::io::{
+    BufRead,
+    BufReader,
+    Read,
+    Write,
+};
+
+use http::{
+    HttpClient,
+    Multipart,
+    MultipartReader,
+};
+
+mod support;
+use support::Server;
+
+
+#[test]
+fn test_multipart_format() {
+    let mut form = Multipart::new();
+    form.add_text("name", "value");
+    form.add_data("file", "a \"b\"\r\n.txt", "text/plain", "Hello, world!");
+
+    let boundary = form.get_boundary().to_owned();
+    assert_eq!(form.get_content_type(), format!("multipart/form-data; boundary={}", &boundary));
+
+    let len = form.get_length().unwrap();
+    let mut body = Vec::new();
+    form.send(&mut body).unwrap();
+    assert_eq!(body.len() as u64, len);
+
+    let expected = format!(concat!(
+        "--{b}\r\n",
+        "Content-Disposition: form-data; name=\"name\"\r\n",
+        "\r\n",
+        "value\r\n",
+        "--{b}\r\n",
+        "Content-Disposition: form-data; name=\"file\"; filename=\"a %22b%22%0D%0A.txt\"\r\n",
+        "Content-Type: text/plain\r\n",
+        "\r\n",
+        "Hello, world!\r\n",
+        "--{b}--\r\n"), b = boundary);
+    assert_eq!(String::from_utf8(body).unwrap(), expected);
+}
+
+
+#[test]
+fn test_multipart_short_file() {
+    let mut form = Multipart::new();
+    form.add_file("file", "a.bin", "application/octet-stream", &b"12345"[..], Some(10));
+    assert!(form.send(&mut Vec::new()).is_err());
+}
+
+
+#[test]
+fn test_multipart_length() {
+    Server::new("127.0.0.1:35600")
+        .step(
+            |request, reader| {
+                assert_eq!(request.get_method(), "POST");
+                let len: usize = request.header.get("content-length").unwrap().parse().unwrap();
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body).unwrap();
+                let body = String::from_utf8(body).unwrap();
+                assert!(body.contains("filename=\"image.bin\"\r\nContent-Type: application/octet-stream\r\n\r\n0123456789\r\n"));
+                assert!(body.ends_with("--\r\n"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut form = Multipart::new();
+    form.add_text("version", "1.2.3");
+    form.add_file("image", "image.bin", "application/octet-stream", &b"0123456789"[..], Some(10));
+
+    let mut client = HttpClient::new("http://127.0.0.1:35600/upgrade").unwrap();
+    client.request.set_method("POST");
+    client.send_multipart(form).unwrap();
+    assert_eq!(client.response.get_code(), 200);
+}
+
+
+#[test]
+fn test_multipart_chunked() {
+    Server::new("127.0.0.1:35601")
+        .step(
+            |request, reader| {
+                assert_eq!(request.header.get("content-length"), None);
+                assert_eq!(request.header.get("transfer-encoding"), Some("chunked"));
+                assert!(request.header.get("content-type").unwrap().starts_with("multipart/form-data; boundary="));
+
+                let mut body = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line)?;
+                    let len = usize::from_str_radix(line.trim(), 16).unwrap();
+                    let mut chunk = vec![0; len + 2];
+                    reader.read_exact(&mut chunk)?;
+                    if len == 0 {
+                        break
+                    }
+                    body.extend_from_slice(&chunk[.. len]);
+                }
+
+                let body = String::from_utf8(body).unwrap();
+                assert!(body.contains("\r\n\r\n0123456789\r\n"));
+                Ok(())
+            },
+            |writer| {
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Length: 0\r\n",
+                    "\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut form = Multipart::new();
+    form.add_file("image", "image.bin", "application/octet-stream", &b"0123456789"[..], None);
+
+    let mut client = HttpClient::new("http://127.0.0.1:35601/upgrade").unwrap();
+    client.request.set_method("POST");
+    client.send_multipart(form).unwrap();
+    assert_eq!(client.response.get_code(), 200);
+}
+
+
+#[test]
+fn test_multipart_boundary() {
+    assert_eq!(Multipart::parse_boundary("multipart/x-mixed-replace; boundary=frame").as_deref(), Some("frame"));
+    assert_eq!(Multipart::parse_boundary("Multipart/Byteranges;Boundary=\"a b\"").as_deref(), Some("a b"));
+    assert_eq!(Multipart::parse_boundary("multipart/form-data").as_deref(), None);
+    assert_eq!(Multipart::parse_boundary("text/plain; boundary=x").as_deref(), None);
+}
+
+
+#[test]
+fn test_multipart_reader() {
+    let mut form = Multipart::new();
+    form.add_text("name", "value");
+    form.add_data("file", "a.bin", "application/octet-stream", &b"\r\n--not a boundary\r\n"[..]);
+    form.add_text("last", "");
+
+    let boundary = form.get_boundary().to_owned();
+    let mut body = Vec::new();
+    form.send(&mut body).unwrap();
+
+    // one byte per read to check delimiter on the buffer edge
+    let mut reader = MultipartReader::new(BufReader::with_capacity(1, body.as_slice()), &boundary);
+
+    let mut part = reader.next_part().unwrap().unwrap();
+    assert_eq!(part.header.get("content-disposition"), Some("form-data; name=\"name\""));
+    let mut data = String::new();
+    part.read_to_string(&mut data).unwrap();
+    assert_eq!(data, "value");
+
+    let mut part = reader.next_part().unwrap().unwrap();
+    assert_eq!(part.header.get("content-type"), Some("application/octet-stream"));
+    let mut data = Vec::new();
+    part.read_to_end(&mut data).unwrap();
+    assert_eq!(data.as_slice(), b"\r\n--not a boundary\r\n");
+
+    // skipped without reading
+    let part = reader.next_part().unwrap().unwrap();
+    assert_eq!(part.header.get("content-disposition"), Some("form-data; name=\"last\""));
+
+    assert!(reader.next_part().unwrap().is_none());
+}
+
+
+#[test]
+fn test_multipart_byteranges() {
+    let body = concat!(
+        "preamble\r\n",
+        "--THIS_STRING_SEPARATES\r\n",
+        "Content-Type: application/pdf\r\n",
+        "Content-Range: bytes 500-503/8000\r\n",
+        "\r\n",
+        "abcd\r\n",
+        "--THIS_STRING_SEPARATES  \r\n",
+        "Content-Type: application/pdf\r\n",
+        "Content-Range: bytes 7000-7001/8000\r\n",
+        "\r\n",
+        "ef\r\n",
+        "--THIS_STRING_SEPARATES--\r\n",
+        "epilogue");
+
+    let mut reader = MultipartReader::new(body.as_bytes(), "THIS_STRING_SEPARATES");
+    let mut ranges = Vec::new();
+    while let Some(mut part) = reader.next_part().unwrap() {
+        let range = part.header.get("content-range").unwrap().to_owned();
+        let mut data = String::new();
+        part.read_to_string(&mut data).unwrap();
+        ranges.push((range, data));
+    }
+
+    assert_eq!(ranges, vec![
+        ("bytes 500-503/8000".to_owned(), "abcd".to_owned()),
+        ("bytes 7000-7001/8000".to_owned(), "ef".to_owned()),
+    ]);
+}
+
+
+#[test]
+fn test_multipart_mjpeg() {
+    Server::new("127.0.0.1:35602")
+        .step(
+            |_request, _reader| Ok(()),
+            |writer| {
+                // stream never ends
+                writer.write_all(concat!(
+                    "HTTP/1.1 200 Ok\r\n",
+                    "Content-Type: multipart/x-mixed-replace; boundary=frame\r\n",
+                    "\r\n",
+                    "--frame\r\n",
+                    "Content-Type: image/jpeg\r\n",
+                    "Content-Length: 6\r\n",
+                    "\r\n",
+                    "frame1\r\n",
+                    "--frame\r\n",
+                    "Content-Type: image/jpeg\r\n",
+                    "Content-Length: 6\r\n",
+                    "\r\n",
+                    "frame2\r\n",
+                    "--frame\r\n"
+                ).as_bytes())
+            })
+        .run();
+
+    let mut client = HttpClient::new("http://127.0.0.1:35602/mjpeg").unwrap();
+    client.get().unwrap();
+
+    let boundary = Multipart::parse_boundary(client.response.header.get("content-type").unwrap()).unwrap();
+    let mut reader = MultipartReader::new(&mut client, &boundary);
+
+    for expected in &["frame1", "frame2"] {
+        let mut part = reader.next_part().unwrap().unwrap();
+        assert_eq!(part.header.get("content-type"), Some("image/jpeg"));
+        let mut frame = String::new();
+        part.read_to_string(&mut frame).unwrap();
+        assert_eq!(&frame, expected);
+    }
+}
@@ -0,0 +1,168 @@
+use http::{
+    Sdp,
+    SdpConnection,
+    SdpError,
+    SdpMedia,
+    SdpRtpMap,
+};
+
+
+/// RFC 8866 5
+static RFC_EXAMPLE: &str = concat!(
+    "v=0\r\n",
+    "o=jdoe 3724394400 3724394405 IN IP4 198.51.100.1\r\n",
+    "s=Call to John Smith\r\n",
+    "i=SDP Offer #1\r\n",
+    "u=http://www.jdoe.example.com/home.html\r\n",
+    "e=Jane Doe <jane@jdoe.example.com>\r\n",
+    "p=+1 617 555-6011\r\n",
+    "c=IN IP4 198.51.100.1\r\n",
+    "t=0 0\r\n",
+    "m=audio 49170 RTP/AVP 0\r\n",
+    "m=audio 49180 RTP/AVP 0\r\n",
+    "m=video 51372 RTP/AVP 99\r\n",
+    "c=IN IP6 2001:db8::2\r\n",
+    "a=rtpmap:99 h263-1998/90000\r\n");
+
+
+static RTSP_EXAMPLE: &str = concat!(
+    "v=0\r\n",
+    "o=- 1586937000000000 1 IN IP4 192.168.1.64\r\n",
+    "s=Media Presentation\r\n",
+    "c=IN IP4 239.0.0.1/16/2\r\n",
+    "b=AS:5100\r\n",
+    "t=0 0\r\n",
+    "r=7d 1h 0 25h\r\n",
+    "a=control:rtsp://192.168.1.64/Streaming/Channels/101/\r\n",
+    "a=range:npt=now-\r\n",
+    "a=recvonly\r\n",
+    "m=video 0 RTP/AVP 96\r\n",
+    "i=Video\r\n",
+    "b=AS:5000\r\n",
+    "a=rtpmap:96 H264/90000\r\n",
+    "a=fmtp:96 profile-level-id=420029; packetization-mode=1; sprop-parameter-sets=Z01AKI2NQDwBE/LgLcBAQFAAAD6AAAw1DoYACYFAABfXgu8uNDAATAoAAL68F3lwoA==,aO44gA==\r\n",
+    "a=control:trackID=1\r\n",
+    "m=audio 0/2 RTP/AVP 97\r\n",
+    "a=rtpmap:97 MPEG4-GENERIC/44100/2\r\n",
+    "a=control:trackID=2\r\n");
+
+
+#[test]
+fn test_parse_rfc() {
+    let sdp = Sdp::parse(RFC_EXAMPLE).unwrap();
+
+    assert_eq!(sdp.origin.username, "jdoe");
+    assert_eq!(sdp.origin.session_id, "3724394400");
+    assert_eq!(sdp.origin.address, "198.51.100.1");
+    assert_eq!(sdp.name, "Call to John Smith");
+    assert_eq!(sdp.info, "SDP Offer #1");
+    assert_eq!(sdp.emails, vec!["Jane Doe <jane@jdoe.example.com>"]);
+    assert_eq!(sdp.connection, Some(SdpConnection::new("198.51.100.1")));
+    assert_eq!(sdp.times.len(), 1);
+    assert_eq!(sdp.media.len(), 3);
+
+    let video = &sdp.media[2];
+    assert_eq!(video.media, "video");
+    assert_eq!(video.port, 51372);
+    assert_eq!(video.formats, vec!["99"]);
+    assert_eq!(video.get_rtpmap("99"), Some(SdpRtpMap {
+        payload: 99,
+        encoding: "h263-1998".to_owned(),
+        clock_rate: 90000,
+        params: String::new(),
+    }));
+
+    assert_eq!(sdp.get_connection(&sdp.media[0]).unwrap().address, "198.51.100.1");
+    let connection = sdp.get_connection(video).unwrap();
+    assert_eq!(connection.addr_type, "IP6");
+    assert_eq!(connection.address, "2001:db8::2");
+
+    assert_eq!(sdp.to_string(), RFC_EXAMPLE);
+}
+
+
+#[test]
+fn test_parse_rtsp() {
+    let sdp = Sdp::parse(RTSP_EXAMPLE).unwrap();
+
+    assert_eq!(sdp.get_control(), Some("rtsp://192.168.1.64/Streaming/Channels/101/"));
+    assert_eq!(sdp.get_range(), Some("npt=now-"));
+    assert_eq!(sdp.get_attribute("recvonly"), Some(""));
+    assert_eq!(sdp.get_attribute("sendonly"), None);
+    assert_eq!(sdp.times[0].repeats, vec!["7d 1h 0 25h"]);
+    assert_eq!(sdp.bandwidth, vec!["AS:5100"]);
+
+    let connection = sdp.connection.as_ref().unwrap();
+    assert_eq!(connection.address, "239.0.0.1");
+    assert_eq!(connection.ttl, Some(16));
+    assert_eq!(connection.count, Some(2));
+
+    let video = &sdp.media[0];
+    assert_eq!(video.title, "Video");
+    assert_eq!(video.get_control(), Some("trackID=1"));
+    assert_eq!(video.get_fmtp_param("96", "packetization-mode"), Some("1"));
+    assert_eq!(video.get_fmtp_param("96", "profile-level-id"), Some("420029"));
+    assert_eq!(video.get_fmtp_param("97", "packetization-mode"), None);
+
+    let audio = &sdp.media[1];
+    assert_eq!(audio.port_count, Some(2));
+    let rtpmap = audio.get_rtpmap("97").unwrap();
+    assert_eq!(rtpmap.encoding, "MPEG4-GENERIC");
+    assert_eq!(rtpmap.clock_rate, 44100);
+    assert_eq!(rtpmap.params, "2");
+
+    assert_eq!(sdp.to_string(), RTSP_EXAMPLE);
+}
+
+
+#[test]
+fn test_parse_lf() {
+    let sdp = Sdp::parse("v=0\no=- 0 0 IN IP4 127.0.0.1\ns=Test\nt=0 0\nm=video 0 RTP/AVP 96\n").unwrap();
+    assert_eq!(sdp.name, "Test");
+    assert_eq!(sdp.media[0].formats, vec!["96"]);
+}
+
+
+#[test]
+fn test_parse_error() {
+    assert!(matches!(Sdp::parse("v=1\r\n"), Err(SdpError::InvalidVersion)));
+    assert!(matches!(Sdp::parse("v=0\r\no=- 0 0 IN IP4\r\n"), Err(SdpError::InvalidFormat(2))));
+    assert!(matches!(Sdp::parse("v=0\r\ninvalid\r\n"), Err(SdpError::InvalidFormat(2))));
+    assert!(matches!(Sdp::parse("v=0\r\nm=video x RTP/AVP 96\r\n"), Err(SdpError::InvalidFormat(2))));
+    assert!(matches!(Sdp::parse("v=0\r\nr=7d 1h 0 25h\r\n"), Err(SdpError::InvalidFormat(2))));
+}
+
+
+#[test]
+fn test_build() {
+    let mut sdp = Sdp::new("Stream");
+    sdp.origin.address = "192.168.1.10".to_owned();
+    sdp.connection = Some(SdpConnection::new("0.0.0.0"));
+    sdp.set_attribute("control", "*");
+    sdp.set_attribute("control", "rtsp://192.168.1.10/stream");
+
+    let mut media = SdpMedia::new("video", 0, "RTP/AVP");
+    media.add_rtpmap(&SdpRtpMap {
+        payload: 96,
+        encoding: "H265".to_owned(),
+        clock_rate: 90000,
+        params: String::new(),
+    });
+    media.add_flag("sendonly");
+    media.set_attribute("control", "track1");
+    sdp.media.push(media);
+
+    let result = concat!(
+        "v=0\r\n",
+        "o=- 0 0 IN IP4 192.168.1.10\r\n",
+        "s=Stream\r\n",
+        "c=IN IP4 0.0.0.0\r\n",
+        "t=0 0\r\n",
+        "a=control:rtsp://192.168.1.10/stream\r\n",
+        "m=video 0 RTP/AVP 96\r\n",
+        "a=rtpmap:96 H265/90000\r\n",
+        "a=sendonly\r\n",
+        "a=control:track1\r\n");
+    assert_eq!(sdp.to_string(), result);
+    assert_eq!(Sdp::parse(result).unwrap(), sdp);
+}
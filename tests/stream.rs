@@ -15,9 +15,7 @@ fn test_write_simulate() {
             cap = 0;
         }
 
-        if buf_len >= len {
-            ()
-        } else {
+        if buf_len < len {
             cap += buf_len
         }
     }
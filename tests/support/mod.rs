@@ -14,7 +14,8 @@ use std::{
     },
 };
 
-use http;
+
+pub mod tls;
 
 
 pub const HELLO_WORLD: &[u8] = b"Hello, world!";
@@ -51,9 +52,9 @@ impl Server {
     }
 
     pub fn run(self) {
-        thread::spawn(move || {
-            let listener = TcpListener::bind(self.addr).unwrap();
+        let listener = TcpListener::bind(&self.addr).unwrap();
 
+        thread::spawn(move || {
             let mut step_id = 0;
 
             'M: loop {
@@ -0,0 +1,177 @@
+use std::{
+    thread,
+    io::{
+        BufReader,
+        Write,
+    },
+    net::TcpListener,
+    path::PathBuf,
+    sync::Once,
+};
+
+use openssl::{
+    asn1::Asn1Time,
+    bn::BigNum,
+    ec::{
+        EcGroup,
+        EcKey,
+    },
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{
+        PKey,
+        Private,
+    },
+    ssl::{
+        SslAcceptor,
+        SslFiletype,
+        SslMethod,
+        SslVerifyMode,
+        SslVersion,
+    },
+    x509::{
+        X509,
+        X509Name,
+        extension::{
+            BasicConstraints,
+            SubjectAlternativeName,
+        },
+    },
+};
+
+
+/// Directory with test certificates:
+/// `ca.pem`, `server.pem`, `server.key`, `client.pem`, `client.key`
+pub fn cert_dir() -> PathBuf {
+    static INIT: Once = Once::new();
+
+    let dir = std::env::temp_dir().join(format!("libhttp-test-{}", std::process::id()));
+    INIT.call_once(|| generate(&dir));
+    dir
+}
+
+
+fn gen_key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+
+fn gen_cert(cn: &str, key: &PKey<Private>, issuer: Option<(&X509, &PKey<Private>)>, serial: u32) -> X509 {
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, cn).unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(serial).unwrap().to_asn1_integer().unwrap();
+    builder.set_serial_number(&serial).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_pubkey(key).unwrap();
+    builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+    builder.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+
+    match issuer {
+        None => {
+            builder.set_issuer_name(&name).unwrap();
+            builder.append_extension(BasicConstraints::new().critical().ca().build().unwrap()).unwrap();
+            builder.sign(key, MessageDigest::sha256()).unwrap();
+        }
+        Some((ca, ca_key)) => {
+            builder.set_issuer_name(ca.subject_name()).unwrap();
+            let san = SubjectAlternativeName::new()
+                .dns(cn)
+                .ip("127.0.0.1")
+                .build(&builder.x509v3_context(Some(ca), None))
+                .unwrap();
+            builder.append_extension(san).unwrap();
+            builder.sign(ca_key, MessageDigest::sha256()).unwrap();
+        }
+    }
+
+    builder.build()
+}
+
+
+fn generate(dir: &PathBuf) {
+    std::fs::create_dir_all(dir).unwrap();
+
+    let ca_key = gen_key();
+    let ca = gen_cert("libhttp test CA", &ca_key, None, 1);
+
+    let server_key = gen_key();
+    let server = gen_cert("localhost", &server_key, Some((&ca, &ca_key)), 2);
+
+    let client_key = gen_key();
+    let client = gen_cert("client", &client_key, Some((&ca, &ca_key)), 3);
+
+    let write = |name: &str, data: Vec<u8>| std::fs::write(dir.join(name), data).unwrap();
+    write("ca.pem", ca.to_pem().unwrap());
+    write("server.pem", server.to_pem().unwrap());
+    write("server.key", server_key.private_key_to_pem_pkcs8().unwrap());
+    write("client.pem", client.to_pem().unwrap());
+    write("client.key", client_key.private_key_to_pem_pkcs8().unwrap());
+}
+
+
+/// TLS server options
+#[derive(Default)]
+pub struct TlsServerConfig {
+    /// Requires client certificate signed by the test CA
+    pub client_cert: bool,
+    /// Maximal protocol version
+    pub max_version: Option<SslVersion>,
+    /// Number of the connections to accept
+    pub connections: usize,
+}
+
+
+/// Starts TLS server. Responds with `Hello, world!` on each request
+pub fn tls_server(addr: &str, config: TlsServerConfig) {
+    let dir = cert_dir();
+
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    acceptor.set_certificate_chain_file(dir.join("server.pem")).unwrap();
+    acceptor.set_private_key_file(dir.join("server.key"), SslFiletype::PEM).unwrap();
+    if config.client_cert {
+        acceptor.set_ca_file(dir.join("ca.pem")).unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+    }
+    acceptor.set_max_proto_version(config.max_version).unwrap();
+    let acceptor = acceptor.build();
+
+    let listener = TcpListener::bind(addr).unwrap();
+
+    thread::spawn(move || {
+        for _ in 0 .. config.connections.max(1) {
+            let (stream, _) = listener.accept().unwrap();
+            let mut stream = match acceptor.accept(stream) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            let mut request = http::Request::default();
+            if request.parse(&mut BufReader::new(&mut stream)).is_err() {
+                continue;
+            }
+
+            let _ = stream.write_all(concat!(
+                "HTTP/1.1 200 Ok\r\n",
+                "Connection: close\r\n",
+                "Content-Length: 13\r\n",
+                "\r\n",
+                "Hello, world!"
+            ).as_bytes());
+            let _ = stream.shutdown();
+        }
+    });
+}
+
+
+/// Returns base64 encoded SHA-256 hash of the certificate public key
+pub fn spki_pin(name: &str) -> String {
+    let pem = std::fs::read(cert_dir().join(name)).unwrap();
+    let cert = X509::from_pem(&pem).unwrap();
+    let spki = cert.public_key().unwrap().public_key_to_der().unwrap();
+    base64::encode(&openssl::hash::hash(MessageDigest::sha256(), &spki).unwrap())
+}